@@ -0,0 +1,140 @@
+//! Assertion helpers for checking a [`RunResult`].
+//!
+//! Each helper panics with a message showing what pars actually did, so they can be used
+//! directly in `#[test]` functions.
+use bstr::{BStr, ByteSlice};
+
+use crate::{Exit, RunResult};
+
+/// Asserts the output lines are exactly `expected`, in order.
+#[track_caller]
+pub fn assert_lines<S: AsRef<str>>(actual: &[String], expected: &[S]) {
+    let expected: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+    assert_eq!(actual, expected, "output lines did not match");
+}
+
+/// Asserts the raw output is exactly `expected`, byte for byte.
+///
+/// On failure both are shown with non-printable bytes escaped.
+#[track_caller]
+pub fn assert_bytes(actual: &[u8], expected: &[u8]) {
+    if actual != expected {
+        // long output is cut down to where the two first differ
+        let start = actual
+            .iter()
+            .zip(expected)
+            .position(|(a, e)| a != e)
+            .unwrap_or(actual.len().min(expected.len()))
+            .saturating_sub(32);
+        let window = |bytes: &[u8]| {
+            let end = bytes.len().min(start + 256);
+            BStr::new(&bytes[start.min(end)..end]).to_owned()
+        };
+        panic!(
+            "output bytes did not match ({} bytes, expected {}), from byte {start}:\n  left: {:?}\n right: {:?}",
+            actual.len(),
+            expected.len(),
+            window(actual),
+            window(expected),
+        );
+    }
+}
+
+/// Asserts the raw output is each of `blocks` in full, one after another in any order, so no
+/// line's output was interleaved with another's.
+///
+/// Each block should start differently (e.g. with a tag naming its line), as they are matched
+/// by looking for the block that the rest of the output starts with.
+#[track_caller]
+pub fn assert_blocks<B: AsRef<[u8]>>(actual: &[u8], blocks: &[B]) {
+    let mut remaining: Vec<&[u8]> = blocks.iter().map(AsRef::as_ref).collect();
+    let mut rest = actual;
+    while !remaining.is_empty() {
+        let Some(i) = remaining.iter().position(|block| rest.starts_with(block)) else {
+            let start = actual.len() - rest.len();
+            panic!(
+                "output from byte {start} is not the whole output of any line still expected, so \
+                 it was cut short or interleaved with another's:\n  found: {:?}\n expected one of: {:#?}",
+                BStr::new(&rest[..rest.len().min(256)]),
+                remaining
+                    .iter()
+                    .map(|block| BStr::new(&block[..block.len().min(64)]))
+                    .collect::<Vec<_>>(),
+            );
+        };
+        rest = &rest[remaining.swap_remove(i).len()..];
+    }
+    assert!(
+        rest.is_empty(),
+        "unexpected output after every line's: {:?}",
+        BStr::new(&rest[..rest.len().min(256)])
+    );
+}
+
+/// Asserts the output lines are `expected` in any order, e.g. when lines run in parallel
+/// finish at the same time.
+#[track_caller]
+pub fn assert_lines_unordered<S: AsRef<str>>(actual: &[String], expected: &[S]) {
+    let mut actual: Vec<&str> = actual.iter().map(String::as_str).collect();
+    let mut expected: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+    actual.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(
+        actual, expected,
+        "output lines did not match (in any order)"
+    );
+}
+
+/// Asserts pars exited with status 0.
+#[track_caller]
+pub fn assert_success(result: &RunResult) {
+    assert!(
+        result.success(),
+        "expected pars to succeed, but it exited with {}\nstderr: {:#?}",
+        result.status,
+        result.stderr
+    );
+}
+
+/// Asserts pars exited with a non-zero status, rather than succeeding or being killed.
+#[track_caller]
+pub fn assert_failure(result: &RunResult) {
+    match result.code() {
+        Some(0) => panic!("expected pars to fail, but it exited with 0"),
+        Some(_) => {}
+        None => panic!("expected pars to fail, but it was {}", result.status),
+    }
+}
+
+/// Asserts pars rejected its input or arguments cleanly: it failed with an error message on
+/// stderr, printed nothing to stdout, and did not panic.
+#[track_caller]
+pub fn assert_clean_error(result: &RunResult) {
+    assert_failure(result);
+    assert!(
+        !result.stderr.is_empty(),
+        "pars failed without printing an error"
+    );
+    assert!(
+        !result.raw_stderr.contains_str("panicked"),
+        "pars panicked:\n{}",
+        result.raw_stderr
+    );
+    assert!(
+        result.stdout.is_empty(),
+        "pars printed output though it failed: {:?}",
+        result.stdout
+    );
+}
+
+/// Asserts pars finished in exactly the given way.
+#[track_caller]
+pub fn assert_exit(result: &RunResult, expected: Exit) {
+    assert_eq!(
+        result.exit(),
+        expected,
+        "pars finished with {}, expected {expected}\nstderr: {:#?}",
+        result.exit(),
+        result.stderr
+    );
+}
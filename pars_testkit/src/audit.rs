@@ -0,0 +1,188 @@
+//! Checks pars leaves nothing running once it has exited.
+//!
+//! While pars runs, an [`Auditor`] keeps a record of every process descended from it and the
+//! files each has open. A process that has already been orphaned is no longer a descendant,
+//! so anything started and orphaned between two samples can be missed.
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{process::descendants, Process};
+
+/// How long processes are given to exit after pars has, before they count as leaked
+const GRACE_PERIOD: Duration = Duration::from_millis(200);
+
+/// A process started by pars that was still around after pars exited.
+#[derive(Debug, Clone)]
+pub struct Leak {
+    pub process: Process,
+    /// Files the process had open, as in `/proc/<pid>/fd`, e.g. `pipe:[1234]`
+    pub fds: Vec<String>,
+    /// TCP ports the process is listening on
+    pub listening: Vec<u16>,
+}
+impl Leak {
+    /// A short description of what was left behind
+    pub fn kind(&self) -> &'static str {
+        let command = self.process.cmdline.split(' ').next().unwrap_or_default();
+        let command = command.rsplit('/').next().unwrap_or_default();
+        if self.process.state == 'Z' {
+            "zombie"
+        } else if command == "ssh" {
+            "ssh session"
+        } else if command == "sh" {
+            "orphaned sh"
+        } else {
+            "orphaned process"
+        }
+    }
+}
+impl fmt::Display for Leak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.process)?;
+        for port in &self.listening {
+            write!(f, ", listening on port {port}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads where each of a process's file descriptors points
+fn read_fds(pid: u32) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(format!("/proc/{pid}/fd")) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| fs::read_link(entry.ok()?.path()).ok())
+        .map(|target| target.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Finds the inodes of listening TCP sockets, and the port each listens on
+fn listening_sockets() -> HashMap<String, u16> {
+    let mut sockets = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(table) = fs::read_to_string(table) else {
+            continue;
+        };
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // `0A` is TCP_LISTEN
+            if fields.len() < 10 || fields[3] != "0A" {
+                continue;
+            }
+            let port = fields[1].rsplit(':').next().unwrap_or_default();
+            if let Ok(port) = u16::from_str_radix(port, 16) {
+                sockets.insert(fields[9].to_string(), port);
+            }
+        }
+    }
+
+    sockets
+}
+
+/// Keeps track of pars' descendants on a background thread until stopped.
+#[derive(Debug)]
+pub(crate) struct Auditor {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<(Process, Vec<String>)>>,
+}
+impl Auditor {
+    pub fn start(pid: u32, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                // each process seen, by pid and start time, with the files it last had open
+                let mut seen: HashMap<(u32, u64), (Process, Vec<String>)> = HashMap::new();
+                while !stop.load(Ordering::Relaxed) {
+                    for process in descendants(pid) {
+                        let fds = read_fds(process.pid);
+                        seen.insert((process.pid, process.start_time), (process, fds));
+                    }
+                    thread::sleep(interval);
+                }
+
+                seen.into_values().collect()
+            }
+        });
+
+        Self { stop, handle }
+    }
+
+    /// Stops auditing once pars has exited, and returns every process seen that is still
+    /// around after a short grace period.
+    pub fn stop(self) -> Vec<Leak> {
+        self.stop.store(true, Ordering::Relaxed);
+        let seen = self.handle.join().unwrap();
+
+        let deadline = Instant::now() + GRACE_PERIOD;
+        let mut remaining = seen;
+        loop {
+            remaining.retain(|(p, _)| {
+                Process::read(p.pid).is_some_and(|now| now.start_time == p.start_time)
+            });
+            if remaining.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let sockets = listening_sockets();
+        remaining
+            .into_iter()
+            .filter_map(|(process, seen_fds)| {
+                // refresh the state, as a process may have become a zombie since it was seen
+                let process = Process::read(process.pid)?;
+                // a zombie has no files open, so fall back to what was open during the run
+                let fds = Some(read_fds(process.pid))
+                    .filter(|fds| !fds.is_empty())
+                    .unwrap_or(seen_fds);
+                let listening = fds
+                    .iter()
+                    .filter_map(|fd| fd.strip_prefix("socket:[")?.strip_suffix(']'))
+                    .filter_map(|inode| sockets.get(inode).copied())
+                    .collect();
+
+                Some(Leak {
+                    process,
+                    fds,
+                    listening,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use crate::ParsProgram;
+
+    #[test]
+    fn test_audit_clean_exit() {
+        let mut program = ParsProgram::builder("sh").audit().spawn().unwrap();
+        program.run_cmds(["sleep 0.1", "echo done"]);
+        assert_eq!(program.finish().stdout, ["done"]);
+    }
+
+    #[test]
+    fn test_audit_finds_orphan() {
+        let mut program = ParsProgram::builder("sh").audit().spawn().unwrap();
+        // the sleep keeps the shell running until the orphan has been seen
+        program.run_cmds(["sleep 30 >/dev/null 2>&1 &", "sleep 0.2"]);
+
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| program.finish())).unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.contains("orphaned process: pid"), "{message}");
+        assert!(message.contains("(sleep 30)"), "{message}");
+    }
+}
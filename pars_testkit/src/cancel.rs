@@ -0,0 +1,123 @@
+//! Lets a test runner clean up after a test it has given up on.
+//!
+//! A test runs on a thread of its own, which cannot be stopped once it has timed out, but
+//! what it started can be. The runner gives each test a [`Token`], made current on the test's
+//! thread with [`Token::enter`], and calls [`Token::cancel`] when it gives up on the test.
+//! That kills every pars process group the test still has running, and gives back anything
+//! else the test holds, such as its stand-in slot (see
+//! [`set_stand_in_limit`](crate::set_stand_in_limit)).
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Token>> = const { RefCell::new(None) };
+}
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// A handle for cancelling one test, shared between the runner and the test's thread.
+#[derive(Clone, Default)]
+pub struct Token(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    next_id: AtomicUsize,
+    callbacks: Mutex<Vec<(usize, Callback)>>,
+}
+
+impl Token {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes this the token of the test running on this thread
+    pub fn enter(self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self));
+    }
+
+    /// Runs everything registered for this token's test, and anything registered from now on
+    /// as soon as it is registered
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        let callbacks = std::mem::take(&mut *self.0.callbacks.lock().unwrap());
+        for (_, callback) in callbacks {
+            callback();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A callback registered with [`on_cancel`], unregistered when dropped
+pub(crate) struct Registration {
+    token: Option<Token>,
+    id: usize,
+}
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            token
+                .0
+                .callbacks
+                .lock()
+                .unwrap()
+                .retain(|&(id, _)| id != self.id);
+        }
+    }
+}
+
+/// Runs `callback` if the test running on this thread is cancelled before the returned
+/// registration is dropped. If the test has already been cancelled, it is run at once.
+pub(crate) fn on_cancel(callback: impl FnOnce() + Send + 'static) -> Registration {
+    let token = CURRENT.with(|current| current.borrow().clone());
+    let Some(token) = token else {
+        return Registration { token: None, id: 0 };
+    };
+
+    let id = token.0.next_id.fetch_add(1, Ordering::Relaxed);
+    let mut callbacks = token.0.callbacks.lock().unwrap();
+    // checked under the lock, so a cancel cannot run between the check and the push
+    if token.is_cancelled() {
+        drop(callbacks);
+        callback();
+        return Registration { token: None, id };
+    }
+    callbacks.push((id, Box::new(callback)));
+    drop(callbacks);
+
+    Registration {
+        token: Some(token),
+        id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+    use crate::{Exit, ParsProgram};
+
+    #[test]
+    fn test_cancel_kills_running_programs() {
+        let token = Token::new();
+        let entered = token.clone();
+        let test = thread::spawn(move || {
+            entered.enter();
+            let mut program = ParsProgram::builder("sleep").arg("30").spawn().unwrap();
+            program.wait_timeout(Duration::from_secs(20))
+        });
+
+        thread::sleep(Duration::from_millis(200));
+        token.cancel();
+        assert_eq!(test.join().unwrap(), Some(Exit::Signal(libc::SIGKILL)));
+    }
+}
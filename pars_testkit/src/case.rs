@@ -0,0 +1,43 @@
+use std::{borrow::Cow, fmt, sync::Arc, time::Duration};
+
+/// A single named test, as run by a test runner. Tests report failure by panicking.
+#[derive(Clone)]
+pub struct TestCase {
+    pub name: Cow<'static, str>,
+    /// Whether the test must not run at the same time as any other test
+    pub serial: bool,
+    /// How long the test may run before it is failed, if different to the runner's default
+    pub timeout: Option<Duration>,
+    pub run: Arc<dyn Fn() + Send + Sync>,
+}
+impl TestCase {
+    pub fn new(name: impl Into<Cow<'static, str>>, run: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            name: name.into(),
+            serial: false,
+            timeout: None,
+            run: Arc::new(run),
+        }
+    }
+
+    /// Marks the test as needing to run on its own
+    pub fn serial(mut self) -> Self {
+        self.serial = true;
+        self
+    }
+
+    /// Sets the timeout of the test, in seconds
+    pub fn timeout(mut self, secs: u64) -> Self {
+        self.timeout = Some(Duration::from_secs(secs));
+        self
+    }
+}
+impl fmt::Debug for TestCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestCase")
+            .field("name", &self.name)
+            .field("serial", &self.serial)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
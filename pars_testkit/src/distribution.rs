@@ -0,0 +1,256 @@
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+use pars_libs::Remote;
+
+use crate::StandIn;
+
+/// The host of a [`RemoteHost::stand_in`] placeholder
+const STAND_IN_HOST: &str = "stand-in";
+
+/// Describes where pars should run its lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    // Local number of threads
+    Local(u32),
+    // Vector tuples (Remote, num_threads)
+    Remote(Vec<RemoteHost>),
+    // Local number of threads, alongside remotes (which may include this machine itself)
+    Mixed(u32, Vec<RemoteHost>),
+}
+impl Distribution {
+    /// Gets the number of lines that can run at once, locally and across every remote
+    pub fn total_threads(&self) -> u32 {
+        self.local_threads() + self.remotes().iter().map(|r| r.threads).sum::<u32>()
+    }
+
+    /// Gets the number of lines that can run at once, as [`Distribution::total_threads`].
+    ///
+    /// This used to count each remote once, however many threads it had.
+    #[deprecated(note = "use `total_threads`, which counts every thread of each remote")]
+    pub fn num_threads(&self) -> u32 {
+        self.total_threads()
+    }
+
+    /// The number of lines run at once locally
+    pub fn local_threads(&self) -> u32 {
+        match self {
+            Distribution::Local(n) | Distribution::Mixed(n, _) => *n,
+            Distribution::Remote(_) => 0,
+        }
+    }
+
+    /// The remotes lines are run on, if any
+    pub fn remotes(&self) -> &[RemoteHost] {
+        match self {
+            Distribution::Local(_) => &[],
+            Distribution::Remote(remotes) | Distribution::Mixed(_, remotes) => remotes,
+        }
+    }
+
+    /// The remotes lines are run on, to be changed in place
+    pub(crate) fn remotes_mut(&mut self) -> &mut [RemoteHost] {
+        match self {
+            Distribution::Local(_) => &mut [],
+            Distribution::Remote(remotes) | Distribution::Mixed(_, remotes) => remotes,
+        }
+    }
+
+    /// Short name for the distribution, e.g. `j2` for 2 local threads, `r1+3` for two
+    /// remotes with 1 and 3 threads, or `j2+r1` for both
+    pub fn label(&self) -> String {
+        let remotes = || {
+            let threads: Vec<_> = self
+                .remotes()
+                .iter()
+                .map(|r| r.threads.to_string())
+                .collect();
+            format!("r{}", threads.join("+"))
+        };
+        match self {
+            Distribution::Local(n) => format!("j{n}"),
+            Distribution::Remote(_) => remotes(),
+            Distribution::Mixed(n, _) => format!("j{n}+{}", remotes()),
+        }
+    }
+
+    /// Whether any remote is a [`RemoteHost::stand_in`] placeholder
+    pub fn uses_stand_in(&self) -> bool {
+        self.remotes().iter().any(RemoteHost::is_stand_in)
+    }
+
+    /// Points every [`RemoteHost::stand_in`] placeholder at `stand_in`
+    pub(crate) fn place_stand_ins(&mut self, stand_in: &StandIn) {
+        for remote in self.remotes_mut().iter_mut().filter(|r| r.is_stand_in()) {
+            *remote = stand_in.remote(remote.threads);
+        }
+    }
+
+    /// The pars arguments selecting this distribution, e.g. `-J 2` or `--remote host:port/threads`,
+    /// with `-J` first when there are both
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Distribution::Local(num) | Distribution::Mixed(num, _) = self {
+            args.push("-J".to_string());
+            args.push(format!("{num}"));
+        }
+        for x in self.remotes() {
+            args.push("--remote".to_string());
+            args.push(x.to_string());
+        }
+
+        args
+    }
+}
+
+/// A remote machine, and the number of threads pars should use on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteHost {
+    pub remote: Remote,
+    pub threads: u32,
+}
+impl RemoteHost {
+    pub fn new(host: impl Into<String>, port: u16, threads: u32) -> Self {
+        Self {
+            remote: Remote {
+                addr: host.into(),
+                port,
+            },
+            threads,
+        }
+    }
+
+    /// A placeholder for a remote running `threads` lines at once, which is run on a stand-in
+    /// of the test's own when pars is spawned (see [`StandIn`]).
+    pub fn stand_in(threads: u32) -> Self {
+        Self::new(STAND_IN_HOST, 0, threads)
+    }
+
+    /// Whether this is a [`RemoteHost::stand_in`] placeholder
+    pub fn is_stand_in(&self) -> bool {
+        self.remote.addr == STAND_IN_HOST && self.remote.port == 0
+    }
+}
+impl FromStr for RemoteHost {
+    type Err = ParseRemoteError;
+
+    /// Parses a remote in the `host:port/threads` form taken by `--remote`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split = s.split([':', '/']);
+
+        // get hostname
+        let hostname = split
+            .next()
+            .filter(|host| !host.is_empty())
+            .ok_or(ParseRemoteError::MissingHost)?
+            .to_string();
+
+        // get port and num threads.
+        let port = split.next().ok_or(ParseRemoteError::MissingPort)?.parse()?;
+
+        let threads = split
+            .next()
+            .ok_or(ParseRemoteError::MissingThreads)?
+            .parse()?;
+
+        Ok(Self {
+            remote: Remote {
+                addr: hostname,
+                port,
+            },
+            threads,
+        })
+    }
+}
+
+/// Why a remote could not be parsed from the `host:port/threads` form.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseRemoteError {
+    MissingHost,
+    MissingPort,
+    MissingThreads,
+    /// The port or number of threads is not a number, or is out of range
+    InvalidNumber(ParseIntError),
+}
+impl From<ParseIntError> for ParseRemoteError {
+    fn from(err: ParseIntError) -> Self {
+        Self::InvalidNumber(err)
+    }
+}
+impl fmt::Display for ParseRemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHost => write!(f, "missing hostname"),
+            Self::MissingPort => write!(f, "missing port"),
+            Self::MissingThreads => write!(f, "missing number of threads"),
+            Self::InvalidNumber(err) => write!(f, "invalid number: {err}"),
+        }
+    }
+}
+impl std::error::Error for ParseRemoteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidNumber(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for RemoteHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}",
+            self.remote.addr, self.remote.port, self.threads
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_round_trip() {
+        let remote: RemoteHost = "localhost:1234/3".parse().unwrap();
+        assert_eq!(remote, RemoteHost::new("localhost", 1234, 3));
+        assert_eq!(remote.to_string(), "localhost:1234/3");
+    }
+
+    #[test]
+    fn test_malformed_remotes() {
+        use ParseRemoteError::*;
+        for (input, expected) in [
+            ("", MissingHost),
+            (":1234/1", MissingHost),
+            ("localhost", MissingPort),
+            ("localhost:1234", MissingThreads),
+            ("localhost/1", MissingThreads),
+        ] {
+            assert_eq!(input.parse::<RemoteHost>(), Err(expected), "{input:?}");
+        }
+        for input in [
+            "localhost:/1",
+            "localhost:port/1",
+            "localhost:99999/1",
+            "localhost:1234/-1",
+        ] {
+            assert!(
+                matches!(input.parse::<RemoteHost>(), Err(InvalidNumber(_))),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_distribution_args() {
+        assert_eq!(Distribution::Local(4).to_args(), ["-J", "4"]);
+        assert_eq!(
+            Distribution::Remote(vec![RemoteHost::new("a", 1, 1), RemoteHost::new("b", 2, 4)])
+                .to_args(),
+            ["--remote", "a:1/1", "--remote", "b:2/4"]
+        );
+        assert_eq!(
+            Distribution::Mixed(2, vec![RemoteHost::new("localhost", 1, 3)]).to_args(),
+            ["-J", "2", "--remote", "localhost:1/3"]
+        );
+    }
+}
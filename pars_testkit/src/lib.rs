@@ -0,0 +1,65 @@
+//! # pars_testkit
+//! A small harness for testing pars (and pars-like programs) as a black box.
+//!
+//! A [`ParsProgram`] is launched with a [`ParsBuilder`], fed input lines with
+//! [`ParsProgram::run_cmds`], and finished with [`ParsProgram::finish`], which returns a
+//! [`RunResult`] holding the exit status and everything the program printed.
+//! The [`assert`] module has helpers for checking a result.
+//!
+//! Most tests are just some input and the output expected for it, and can be declared in
+//! a few lines with [`pars_test!`], which produces a [`TestCase`] for a test runner.
+//! A scenario that should hold for many distributions and halt modes can instead be declared
+//! once and expanded across a [`Matrix`].
+//!
+//! # Example
+//! ```no_run
+//! use pars_testkit::{assert::assert_lines, Distribution, ParsProgram, TerminationType};
+//!
+//! let mut program = ParsProgram::builder("./target/debug/pars")
+//!     .distribution(Distribution::Local(2))
+//!     .halt(TerminationType::Lazy)
+//!     .spawn()
+//!     .expect("failed to start pars");
+//!
+//! program.run_cmds(["sleep 1; echo 2", "echo 1"]);
+//! let result = program.finish();
+//!
+//! assert_lines(&result.stdout, &["1", "2"]);
+//! assert!(result.success());
+//! ```
+pub mod assert;
+mod audit;
+pub mod cancel;
+mod case;
+mod distribution;
+mod macros;
+mod matrix;
+mod monitor;
+mod process;
+mod program;
+pub mod report;
+mod result;
+mod scenario;
+mod standin;
+mod temp;
+mod termination;
+pub mod trace;
+
+pub use audit::Leak;
+pub use bstr;
+pub use case::TestCase;
+pub use distribution::{Distribution, ParseRemoteError, RemoteHost};
+pub use libc;
+pub use matrix::{Cell, Matrix};
+pub use monitor::{cpu_time, ProcStats};
+pub use pars_libs::Remote;
+pub use process::{children, descendants, Process};
+pub use program::{ParsBuilder, ParsProgram, AUDIT_VAR, BINARY_VAR, TRACE_VAR};
+pub use result::{Exit, RunResult};
+pub use scenario::Scenario;
+pub use standin::{
+    set_stand_in_limit, ssh_main, ClientKey, Disconnect, KnownHost, StandIn, StandInBuilder,
+    STAND_IN_DISCONNECT_VAR, STAND_IN_LOG_VAR,
+};
+pub use temp::TempDir;
+pub use termination::TerminationType;
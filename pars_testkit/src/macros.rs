@@ -0,0 +1,81 @@
+/// Declares a test that feeds lines to pars and checks what it printed.
+///
+/// The macro expands to a function returning a [`TestCase`](crate::TestCase) with the given
+/// name, which runs a [`Scenario`](crate::Scenario) against the binary in `PARS_BINARY`.
+///
+/// The `halt` field is optional. The check is one of:
+/// - `expect: [..]` - stdout must be exactly these lines, in order
+/// - `expect_unordered: [..]` - stdout must be these lines, in any order
+/// - `check: |result| ..` - any closure taking the [`RunResult`](crate::RunResult)
+///
+/// Doc comments come first, followed by any of the runner attributes `#[serial]` and
+/// `#[timeout(secs)]`.
+///
+/// # Example
+/// ```
+/// use pars_testkit::{pars_test, Distribution, TerminationType};
+///
+/// pars_test! {
+///     /// lazy mode: the current line should finish
+///     #[timeout(10)]
+///     test_lazy_mode {
+///         distribution: Distribution::Local(2),
+///         halt: TerminationType::Lazy,
+///         input: ["sleep 1; echo 2", "echo 1; false", "echo 3"],
+///         expect: ["1", "2"],
+///     }
+/// }
+///
+/// let case = test_lazy_mode();
+/// assert_eq!(case.name, "test_lazy_mode");
+/// ```
+#[macro_export]
+macro_rules! pars_test {
+    // collect doc comments, and turn runner attributes into `TestCase` builder calls
+    (@attrs [$($docs:tt)*] [$($mods:tt)*] #[doc = $doc:expr] $($rest:tt)*) => {
+        $crate::pars_test!(@attrs [$($docs)* #[doc = $doc]] [$($mods)*] $($rest)*);
+    };
+    (@attrs [$($docs:tt)*] [$($mods:tt)*] #[serial] $($rest:tt)*) => {
+        $crate::pars_test!(@attrs [$($docs)*] [$($mods)* .serial()] $($rest)*);
+    };
+    (@attrs [$($docs:tt)*] [$($mods:tt)*] #[timeout($secs:expr)] $($rest:tt)*) => {
+        $crate::pars_test!(@attrs [$($docs)*] [$($mods)* .timeout($secs)] $($rest)*);
+    };
+    (@attrs [$($docs:tt)*] [$($mods:tt)*]
+        $name:ident {
+            distribution: $dist:expr,
+            $(halt: $halt:expr,)?
+            input: [$($input:expr),* $(,)?],
+            $kind:ident: $($check:tt)+
+        }
+    ) => {
+        $($docs)*
+        pub fn $name() -> $crate::TestCase {
+            $crate::TestCase::new(stringify!($name), || {
+                let halt: Option<$crate::TerminationType> = None $(.or(Some($halt)))?;
+                let input: &[&str] = &[$($input),*];
+                $crate::Scenario::new($dist, halt, input.iter().copied())
+                    .run($crate::pars_test!(@check $kind $($check)+));
+            })
+            $($mods)*
+        }
+    };
+
+    (@check expect [$($line:expr),* $(,)?] $(,)?) => {
+        |result: &$crate::RunResult| {
+            $crate::assert::assert_lines::<&str>(&result.stdout, &[$($line),*])
+        }
+    };
+    (@check expect_unordered [$($line:expr),* $(,)?] $(,)?) => {
+        |result: &$crate::RunResult| {
+            $crate::assert::assert_lines_unordered::<&str>(&result.stdout, &[$($line),*])
+        }
+    };
+    (@check check $check:expr $(,)?) => {
+        $check
+    };
+
+    ($($test:tt)*) => {
+        $crate::pars_test!(@attrs [] [] $($test)*);
+    };
+}
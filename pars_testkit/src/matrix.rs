@@ -0,0 +1,128 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use crate::{Distribution, RemoteHost, RunResult, Scenario, TerminationType, TestCase};
+
+/// One combination of distribution and halt mode from a [`Matrix`].
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub distribution: Distribution,
+    pub halt: Option<TerminationType>,
+}
+impl Cell {
+    /// The halt mode pars should use, taking `never` as the default
+    pub fn effective_halt(&self) -> TerminationType {
+        self.halt.unwrap_or(TerminationType::Never)
+    }
+
+    /// Short name for the cell, e.g. `j2/lazy` or `r1+3/default`
+    pub fn label(&self) -> String {
+        let halt = self.halt.map_or("default", |h| h.as_str());
+
+        format!("{}/{halt}", self.distribution.label())
+    }
+}
+
+/// A set of distributions and halt modes, which a scenario can be expanded across.
+///
+/// Each scenario is declared once with [`Matrix::expand`], which produces one [`TestCase`]
+/// for every combination (a [`Cell`]). The check is given the cell, so the expected output
+/// can either be shared or computed from the distribution and halt mode.
+///
+/// # Example
+/// ```
+/// use pars_testkit::{assert::assert_lines, Matrix, TerminationType};
+///
+/// let cases = Matrix::new()
+///     .local(1..=2)
+///     .halts(TerminationType::ALL)
+///     .expand("simple", &["echo 1; false; echo 2"], |_cell, result| {
+///         assert_lines(&result.stdout, &["1"]);
+///     });
+/// assert_eq!(cases.len(), 6);
+/// assert_eq!(cases[0].name, "simple/j1/never");
+/// ```
+#[derive(Debug, Default)]
+pub struct Matrix {
+    distributions: Vec<Distribution>,
+    halts: Vec<Option<TerminationType>>,
+}
+impl Matrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `Distribution::Local(n)` for each `n` in `threads`
+    pub fn local(mut self, threads: RangeInclusive<u32>) -> Self {
+        self.distributions.extend(threads.map(Distribution::Local));
+        self
+    }
+
+    /// Adds a single distribution, e.g. one or more remotes
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distributions.push(distribution);
+        self
+    }
+
+    /// Adds a run without any `--halt` flag, to check the default
+    pub fn default_halt(mut self) -> Self {
+        self.halts.push(None);
+        self
+    }
+
+    /// Adds each of the given halt modes
+    pub fn halts(mut self, halts: impl IntoIterator<Item = TerminationType>) -> Self {
+        self.halts.extend(halts.into_iter().map(Some));
+        self
+    }
+
+    /// Every cell of the matrix, distribution-major
+    pub fn cells(&self) -> Vec<Cell> {
+        // with no halt modes given, only the default is run
+        let halts: &[Option<TerminationType>] = if self.halts.is_empty() {
+            &[None]
+        } else {
+            &self.halts
+        };
+
+        self.distributions
+            .iter()
+            .flat_map(|distribution| {
+                halts.iter().map(|&halt| Cell {
+                    distribution: distribution.clone(),
+                    halt,
+                })
+            })
+            .collect()
+    }
+
+    /// Creates a test for every cell, each named `<name>/<cell label>`, which gives `input`
+    /// to pars and passes the cell and result to `check`.
+    ///
+    /// Cells with more than one remote are marked serial, as their remotes may be shared,
+    /// unless every remote is a stand-in of the test's own.
+    pub fn expand<F>(&self, name: &str, input: &[&str], check: F) -> Vec<TestCase>
+    where
+        F: Fn(&Cell, &RunResult) + Send + Sync + 'static,
+    {
+        let check = Arc::new(check);
+        self.cells()
+            .into_iter()
+            .map(|cell| {
+                let scenario =
+                    Scenario::new(cell.distribution.clone(), cell.halt, input.iter().copied());
+                let remotes = cell.distribution.remotes();
+                let serial = remotes.len() > 1 && !remotes.iter().all(RemoteHost::is_stand_in);
+                let check = Arc::clone(&check);
+
+                let case = TestCase::new(format!("{name}/{}", cell.label()), move || {
+                    scenario.run(|result| check(&cell, result));
+                });
+                if serial {
+                    case.serial()
+                } else {
+                    case
+                }
+            })
+            .collect()
+    }
+}
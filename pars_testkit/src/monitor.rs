@@ -0,0 +1,121 @@
+//! Samples the pars process through `/proc` while it runs.
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::process::children;
+
+/// What was seen of the pars process while it was being sampled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcStats {
+    /// Peak resident set size of pars, in KiB (`VmHWM`)
+    pub peak_rss_kb: u64,
+    /// Most threads pars had at once, from `/proc/<pid>/task`
+    pub peak_threads: usize,
+    /// Most child processes (such as the commands it runs) pars had running at once
+    pub peak_children: usize,
+    /// Number of times `/proc` was sampled
+    pub samples: usize,
+}
+
+/// Reads a `kB` field such as `VmHWM` from `/proc/<pid>/status`
+fn read_status_kb(pid: u32, field: &str) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Counts the threads of a process
+fn count_threads(pid: u32) -> Option<usize> {
+    Some(fs::read_dir(format!("/proc/{pid}/task")).ok()?.count())
+}
+
+/// Reads the CPU time a process has used so far, in user and kernel mode, across all its
+/// threads. Time used by its children is not included.
+pub fn cpu_time(pid: u32) -> Option<Duration> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // skip past the command name, which may contain spaces
+    let (_, fields) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    // utime and stime are fields 14 and 15, counting the pid as field 1
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+
+    // SAFETY: sysconf has no memory safety requirements
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let ticks_per_sec = u64::try_from(ticks_per_sec).ok().filter(|&t| t > 0)?;
+    Some(Duration::from_secs_f64(
+        (utime + stime) as f64 / ticks_per_sec as f64,
+    ))
+}
+
+/// Samples a process on a background thread until stopped.
+#[derive(Debug)]
+pub(crate) struct Monitor {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<ProcStats>,
+}
+impl Monitor {
+    pub fn start(pid: u32, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                let mut stats = ProcStats::default();
+                while !stop.load(Ordering::Relaxed) {
+                    // the high water mark only grows, so the last sample before exit is the peak
+                    if let Some(rss) = read_status_kb(pid, "VmHWM") {
+                        stats.peak_rss_kb = stats.peak_rss_kb.max(rss);
+                        stats.samples += 1;
+                    }
+                    if let Some(threads) = count_threads(pid) {
+                        stats.peak_threads = stats.peak_threads.max(threads);
+                    }
+                    stats.peak_children = stats.peak_children.max(children(pid).len());
+                    thread::sleep(interval);
+                }
+
+                stats
+            }
+        });
+
+        Self { stop, handle }
+    }
+
+    pub fn stop(self) -> ProcStats {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{process, time::Instant};
+
+    use super::*;
+
+    #[test]
+    fn test_cpu_time_counts_busy_time() {
+        let before = cpu_time(process::id()).unwrap();
+        let start = Instant::now();
+        // spins until enough is counted, as other tests may share the CPU meanwhile
+        let mut used = Duration::ZERO;
+        while used < Duration::from_millis(150) && start.elapsed() < Duration::from_secs(10) {
+            std::hint::spin_loop();
+            used = cpu_time(process::id()).unwrap() - before;
+        }
+        assert!(used >= Duration::from_millis(150), "only {used:?} counted");
+    }
+}
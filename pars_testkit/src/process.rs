@@ -0,0 +1,95 @@
+//! Finds the processes pars has started, through `/proc`.
+use std::{fmt, fs};
+
+/// A process, as seen in `/proc` when it was read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Process state from `/proc/<pid>/stat`, e.g. `R`, `S`, or `Z` for a zombie
+    pub state: char,
+    /// Time the process started, in clock ticks since boot. Together with the pid, this
+    /// identifies a process even once its pid has been reused.
+    pub start_time: u64,
+    /// The command line, with arguments separated by spaces
+    pub cmdline: String,
+}
+impl Process {
+    /// Reads a process from `/proc`, or `None` if it no longer exists
+    pub fn read(pid: u32) -> Option<Self> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // the command name is in parentheses and may itself contain spaces or parentheses
+        let (_, fields) = stat.rsplit_once(')')?;
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+
+        let cmdline = fs::read(format!("/proc/{pid}/cmdline")).unwrap_or_default();
+        let cmdline = String::from_utf8_lossy(&cmdline)
+            .trim_end_matches('\0')
+            .replace('\0', " ");
+
+        Some(Self {
+            pid,
+            state: fields.first()?.chars().next()?,
+            ppid: fields.get(1)?.parse().ok()?,
+            start_time: fields.get(19)?.parse().ok()?,
+            cmdline,
+        })
+    }
+
+    /// Returns true if this process has not exited (or become a zombie) since it was read
+    pub fn is_running(&self) -> bool {
+        Process::read(self.pid)
+            .is_some_and(|now| now.start_time == self.start_time && now.state != 'Z')
+    }
+
+    /// Sends `SIGKILL` to this process, if it is still running
+    pub fn kill(&self) {
+        if self.is_running() {
+            // SAFETY: kill has no memory safety requirements
+            unsafe { libc::kill(self.pid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+}
+impl fmt::Display for Process {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pid {} ({})", self.pid, self.cmdline)
+    }
+}
+
+/// Reads every process currently in `/proc`
+fn all_processes() -> Vec<Process> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .filter_map(Process::read)
+        .collect()
+}
+
+/// Finds the processes `pid` has started that are still running, not counting zombies
+pub fn children(pid: u32) -> Vec<Process> {
+    all_processes()
+        .into_iter()
+        .filter(|p| p.ppid == pid && p.state != 'Z')
+        .collect()
+}
+
+/// Finds every process descended from `pid`: its children, their children, and so on.
+///
+/// A process whose parent has exited is adopted by another process, so it is only found
+/// if it is read while its parent is still running.
+pub fn descendants(pid: u32) -> Vec<Process> {
+    let all = all_processes();
+
+    let mut found = Vec::new();
+    let mut parents = vec![pid];
+    while let Some(parent) = parents.pop() {
+        for process in all.iter().filter(|p| p.ppid == parent) {
+            parents.push(process.pid);
+            found.push(process.clone());
+        }
+    }
+
+    found
+}
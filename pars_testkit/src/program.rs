@@ -0,0 +1,443 @@
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, Read, Write},
+    os::unix::process::CommandExt,
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use bstr::BString;
+
+use crate::{
+    audit::{Auditor, Leak},
+    cancel::{self, Registration},
+    monitor::Monitor,
+    process::descendants,
+    standin,
+    trace::{self, Tracer},
+    Distribution, Exit, Process, RunResult, TerminationType,
+};
+
+/// The environment variable read by [`ParsProgram::from_env`]
+pub const BINARY_VAR: &str = "PARS_BINARY";
+/// When this environment variable is set, [`ParsProgram::from_env`] turns on
+/// [`ParsBuilder::audit`]
+pub const AUDIT_VAR: &str = "PARS_AUDIT";
+/// When this environment variable is set to a directory, [`ParsProgram::from_env`] turns on
+/// [`ParsBuilder::trace`], with a trace file there named after the current thread
+pub const TRACE_VAR: &str = "PARS_TRACE";
+/// How often the processes started by pars are looked for while auditing
+const AUDIT_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Configures and launches a [`ParsProgram`].
+///
+/// Created with [`ParsProgram::builder`].
+#[derive(Debug)]
+pub struct ParsBuilder {
+    binary: PathBuf,
+    distribution: Option<Distribution>,
+    halt: Option<TerminationType>,
+    args: Vec<OsString>,
+    monitor: Option<Duration>,
+    audit: bool,
+    trace: Option<PathBuf>,
+    env_clear: bool,
+    envs: Vec<(OsString, OsString)>,
+    current_dir: Option<PathBuf>,
+}
+impl ParsBuilder {
+    /// Sets where pars should run its lines. If not set, no `-J` or `--remote` flags are given.
+    pub fn distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = Some(distribution);
+        self
+    }
+
+    /// Sets the `--halt` mode. If not set, no `--halt` flag is given.
+    pub fn halt(mut self, halt: TerminationType) -> Self {
+        self.halt = Some(halt);
+        self
+    }
+
+    /// Like [`ParsBuilder::halt`], but leaves the flag out when given `None`
+    pub fn halt_opt(mut self, halt: Option<TerminationType>) -> Self {
+        self.halt = halt;
+        self
+    }
+
+    /// Adds an extra argument, given after the distribution and halt flags
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds extra arguments, given after the distribution and halt flags
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for pars, and so for the commands it runs locally
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Starts pars with an empty environment, apart from variables set with
+    /// [`ParsBuilder::env`]. Without `PATH`, pars will not find most commands, and without
+    /// `HOME`, ssh will not find its key.
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self
+    }
+
+    /// Sets the working directory pars is started in
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Samples pars through `/proc` every `interval` while it runs.
+    /// The results are given in [`RunResult::proc_stats`].
+    pub fn monitor(mut self, interval: Duration) -> Self {
+        self.monitor = Some(interval);
+        self
+    }
+
+    /// Audits pars' teardown: keeps track of every process pars starts, and their open files,
+    /// then checks none of them are still around once pars has exited.
+    ///
+    /// [`ParsProgram::finish`] panics with a list of anything left behind, such as zombies,
+    /// orphaned `sh` processes, ssh sessions or listening sockets.
+    pub fn audit(mut self) -> Self {
+        self.audit = true;
+        self
+    }
+
+    /// Puts a proxy in front of each remote in the distribution, which appends everything sent
+    /// either way, with timestamps, to the trace file at `path` (see [`trace`]).
+    ///
+    /// ssh checks the host key of the proxy's address rather than the remote's. If pars is
+    /// given a `HOME` with [`ParsBuilder::env`], as a stand-in gives it, the proxies are added
+    /// to its `known_hosts`; otherwise ssh must already accept them.
+    pub fn trace(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trace = Some(path.into());
+        self
+    }
+
+    /// The full argument list pars will be started with
+    pub fn argv(&self) -> Vec<OsString> {
+        let mut argv: Vec<OsString> = Vec::new();
+        if let Some(distribution) = &self.distribution {
+            argv.extend(distribution.to_args().into_iter().map(Into::into));
+        }
+        if let Some(halt) = self.halt {
+            argv.push("--halt".into());
+            argv.push(halt.as_str().into());
+        }
+        argv.extend(self.args.iter().cloned());
+
+        argv
+    }
+
+    /// Starts pars with its stdin, stdout and stderr all piped back to the harness.
+    ///
+    /// pars is started in a process group of its own, as a shell would start it, so
+    /// [`ParsProgram::signal_group`] reaches pars and everything it starts.
+    ///
+    /// Any [`RemoteHost::stand_in`](crate::RemoteHost::stand_in) remotes are run on this
+    /// thread's own stand-in, which is started if need be.
+    pub fn spawn(mut self) -> io::Result<ParsProgram> {
+        if self
+            .distribution
+            .as_ref()
+            .is_some_and(Distribution::uses_stand_in)
+        {
+            let stand_in = standin::current()?;
+            if let Some(distribution) = &mut self.distribution {
+                distribution.place_stand_ins(&stand_in);
+            }
+            self = stand_in.setup(self);
+        }
+        let tracer = match (&self.trace, &mut self.distribution) {
+            (Some(path), Some(distribution)) if !distribution.remotes().is_empty() => {
+                let tracer = Tracer::start(path, distribution.remotes_mut())?;
+                if let Some((_, home)) = self.envs.iter().rev().find(|(key, _)| key == "HOME") {
+                    tracer.trust(home.as_ref())?;
+                }
+                Some(tracer)
+            }
+            _ => None,
+        };
+
+        let mut cmd = Command::new(&self.binary);
+        cmd.args(self.argv());
+        cmd.process_group(0);
+        if self.env_clear {
+            cmd.env_clear();
+        }
+        cmd.envs(self.envs.iter().map(|(k, v)| (k, v)));
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+
+        // Set up stdin, stdout and stderr as separate streams
+        cmd.stdin(Stdio::piped());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        let started = Instant::now();
+        let mut child = cmd.spawn()?;
+        // a test the runner gives up on cannot be stopped, but its pars can
+        let group = child.id() as libc::pid_t;
+        let cancel = cancel::on_cancel(move || {
+            let _ = send_signal(-group, libc::SIGKILL);
+        });
+        let stdin = child.stdin.take();
+        // read output as it is produced, so pars never blocks on a full pipe
+        let stdout = child.stdout.take().map(read_all);
+        let stderr = child.stderr.take().map(read_all);
+        let monitor = self
+            .monitor
+            .map(|interval| Monitor::start(child.id(), interval));
+        let auditor = self
+            .audit
+            .then(|| Auditor::start(child.id(), AUDIT_INTERVAL));
+
+        Ok(ParsProgram {
+            child,
+            stdin,
+            stdout,
+            stderr,
+            monitor,
+            auditor,
+            tracer,
+            _cancel: cancel,
+            started,
+        })
+    }
+}
+
+/// Collects everything written to a stream, byte for byte, on a background thread.
+fn read_all(mut stream: impl Read + Send + 'static) -> JoinHandle<BString> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        // a read error means pars' end of the pipe is gone, so keep what was read
+        let _ = stream.read_to_end(&mut output);
+
+        BString::from(output)
+    })
+}
+
+/// A running pars process.
+///
+/// The process is killed if it is dropped before it has finished.
+pub struct ParsProgram {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Option<JoinHandle<BString>>,
+    stderr: Option<JoinHandle<BString>>,
+    monitor: Option<Monitor>,
+    auditor: Option<Auditor>,
+    tracer: Option<Tracer>,
+    // kills pars' process group if the test is cancelled while this is alive
+    _cancel: Registration,
+    started: Instant,
+}
+impl ParsProgram {
+    /// Starts configuring a run of the pars executable at `binary`
+    pub fn builder(binary: impl Into<PathBuf>) -> ParsBuilder {
+        ParsBuilder {
+            binary: binary.into(),
+            distribution: None,
+            halt: None,
+            args: Vec::new(),
+            monitor: None,
+            audit: false,
+            trace: None,
+            env_clear: false,
+            envs: Vec::new(),
+            current_dir: None,
+        }
+    }
+
+    /// Starts configuring a run of the pars executable named by the `PARS_BINARY`
+    /// environment variable. If `PARS_AUDIT` is set, the run is audited, and if `PARS_TRACE`
+    /// is set, the run is traced to a file in that directory named after the current thread,
+    /// such as the test it runs.
+    ///
+    /// # Panics
+    /// Panics if `PARS_BINARY` is not set.
+    pub fn from_env() -> ParsBuilder {
+        let binary = env::var_os(BINARY_VAR)
+            .unwrap_or_else(|| panic!("{BINARY_VAR} must be set to the path of pars"));
+        let mut builder = Self::builder(binary);
+        if env::var_os(AUDIT_VAR).is_some() {
+            builder = builder.audit();
+        }
+        if let Some(dir) = env::var_os(TRACE_VAR) {
+            let thread = thread::current();
+            let name = trace::file_name(thread.name().unwrap_or("pars"));
+            builder = builder.trace(PathBuf::from(dir).join(name));
+        }
+
+        builder
+    }
+
+    /// The process id of pars
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Writes each command to pars' stdin as its own line.
+    ///
+    /// # Panics
+    /// Panics if stdin has already been closed, or pars has stopped reading it.
+    pub fn run_cmds<I, S>(&mut self, commands: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let stdin = self.stdin.as_mut().expect("stdin already closed");
+        for command in commands {
+            let buf = format!("{}\n", command.as_ref()); // Add a newline to simulate Enter key
+            stdin.write_all(buf.as_bytes()).unwrap();
+            stdin.flush().unwrap();
+        }
+    }
+
+    /// Writes raw bytes to pars' stdin, without adding a newline.
+    ///
+    /// # Panics
+    /// Panics if stdin has already been closed, or pars has stopped reading it.
+    pub fn write_input(&mut self, input: impl AsRef<[u8]>) {
+        let stdin = self.stdin.as_mut().expect("stdin already closed");
+        stdin.write_all(input.as_ref()).unwrap();
+        stdin.flush().unwrap();
+    }
+
+    /// Like [`ParsProgram::write_input`], but returns an error instead of panicking, for
+    /// when pars may have exited before reading its input.
+    pub fn try_write_input(&mut self, input: impl AsRef<[u8]>) -> io::Result<()> {
+        let stdin = self.stdin.as_mut().expect("stdin already closed");
+        stdin.write_all(input.as_ref())?;
+        stdin.flush()
+    }
+
+    /// Closes stdin, as at the end of the input, without waiting for pars to exit
+    pub fn close_input(&mut self) {
+        drop(self.stdin.take());
+    }
+
+    /// Closes stdin, waits for pars to exit, and collects its output.
+    ///
+    /// # Panics
+    /// Panics if called more than once, or if the run is audited and pars left processes
+    /// behind. Those processes are killed first.
+    pub fn finish(&mut self) -> RunResult {
+        drop(self.stdin.take());
+        let status = self.child.wait().expect("Failed to wait for child process");
+        let duration = self.started.elapsed();
+        drop(self.tracer.take());
+
+        // left running, leaked processes could hold the output pipes open forever
+        let leaks = self.auditor.take().map(Auditor::stop).unwrap_or_default();
+        for leak in &leaks {
+            leak.process.kill();
+        }
+
+        let stdout = self.stdout.take().expect("already finished");
+        let stderr = self.stderr.take().expect("already finished");
+
+        let mut result = RunResult::new(
+            status,
+            stdout.join().unwrap(),
+            stderr.join().unwrap(),
+            duration,
+        );
+        result.proc_stats = self.monitor.take().map(Monitor::stop);
+        if !leaks.is_empty() {
+            panic!(
+                "pars left processes behind after exiting:\n{}",
+                describe_leaks(&leaks)
+            );
+        }
+
+        result
+    }
+
+    /// Closes stdin, waits for pars to exit, and returns the lines it printed.
+    pub fn get_stdout(&mut self) -> Vec<String> {
+        self.finish().stdout
+    }
+
+    /// Sends `signal` (e.g. [`libc::SIGTERM`]) to pars alone, as `kill` would.
+    pub fn signal(&self, signal: i32) -> io::Result<()> {
+        send_signal(self.id() as libc::pid_t, signal)
+    }
+
+    /// Sends `signal` to pars' whole process group, as pressing Ctrl-C (for `SIGINT`) in a
+    /// terminal would. Any command pars started without a group of its own receives it too.
+    pub fn signal_group(&self, signal: i32) -> io::Result<()> {
+        send_signal(-(self.id() as libc::pid_t), signal)
+    }
+
+    /// Waits up to `timeout` for pars to exit, returning how it finished or `None` if it
+    /// is still running. Unlike [`ParsProgram::finish`], this does not close stdin.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Option<Exit> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) => return Some(status.into()),
+                Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+                _ => return None,
+            }
+        }
+    }
+
+    /// Every process currently descended from pars, such as the commands it is running
+    pub fn descendants(&self) -> Vec<Process> {
+        descendants(self.id())
+    }
+
+    /// Kills pars, if it is still running.
+    pub fn kill(self) {
+        drop(self);
+    }
+}
+/// Lists leaked processes one per line, with the files each had open
+fn describe_leaks(leaks: &[Leak]) -> String {
+    let mut out = String::new();
+    for leak in leaks {
+        out.push_str(&format!("  {leak}\n"));
+        if !leak.fds.is_empty() {
+            out.push_str(&format!("    open files: {}\n", leak.fds.join(", ")));
+        }
+    }
+
+    out
+}
+
+fn send_signal(pid: libc::pid_t, signal: i32) -> io::Result<()> {
+    // SAFETY: kill has no memory safety requirements
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+impl Drop for ParsProgram {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
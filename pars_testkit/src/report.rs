@@ -0,0 +1,19 @@
+//! Measurements recorded by a test, to be shown alongside its result.
+//!
+//! A test calls [`record`] from the thread it runs on, and the runner collects the values
+//! with [`take`] once the test has finished.
+use std::{cell::RefCell, fmt::Display};
+
+thread_local! {
+    static NOTES: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records a named value for the test running on this thread
+pub fn record(key: impl Into<String>, value: impl Display) {
+    NOTES.with(|notes| notes.borrow_mut().push((key.into(), value.to_string())));
+}
+
+/// Removes and returns everything recorded on this thread
+pub fn take() -> Vec<(String, String)> {
+    NOTES.with(|notes| notes.take())
+}
@@ -0,0 +1,89 @@
+use std::{fmt, os::unix::process::ExitStatusExt, process::ExitStatus, time::Duration};
+
+use bstr::{BString, ByteSlice};
+
+use crate::ProcStats;
+
+/// How a process finished: with an exit code, or killed by a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Code(i32),
+    Signal(i32),
+}
+impl From<ExitStatus> for Exit {
+    fn from(status: ExitStatus) -> Self {
+        match status.code() {
+            Some(code) => Exit::Code(code),
+            // a process either exits or is killed, so there is always one or the other
+            None => Exit::Signal(status.signal().unwrap_or_default()),
+        }
+    }
+}
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exit::Code(code) => write!(f, "exit code {code}"),
+            Exit::Signal(signal) => write!(f, "signal {signal}"),
+        }
+    }
+}
+
+/// Everything observed about a finished run of pars.
+#[derive(Debug)]
+pub struct RunResult {
+    pub status: ExitStatus,
+    /// Lines pars printed to stdout, without their trailing newlines.
+    /// Bytes that are not valid UTF-8 are replaced with `U+FFFD`.
+    pub stdout: Vec<String>,
+    /// Lines pars printed to stderr, as for `stdout`
+    pub stderr: Vec<String>,
+    /// Everything pars printed to stdout, byte for byte
+    pub raw_stdout: BString,
+    /// Everything pars printed to stderr, byte for byte
+    pub raw_stderr: BString,
+    /// Time from spawning pars to it exiting
+    pub duration: Duration,
+    /// What was sampled from `/proc`, if pars was monitored
+    pub proc_stats: Option<ProcStats>,
+}
+impl RunResult {
+    pub fn new(
+        status: ExitStatus,
+        raw_stdout: BString,
+        raw_stderr: BString,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            status,
+            stdout: lossy_lines(&raw_stdout),
+            stderr: lossy_lines(&raw_stderr),
+            raw_stdout,
+            raw_stderr,
+            duration,
+            proc_stats: None,
+        }
+    }
+
+    /// Returns true if pars exited with status 0
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// The exit code of pars, or `None` if it was killed by a signal
+    pub fn code(&self) -> Option<i32> {
+        self.status.code()
+    }
+
+    /// How pars finished
+    pub fn exit(&self) -> Exit {
+        Exit::from(self.status)
+    }
+}
+
+/// Splits output into lines as `BufRead::lines` would, but keeps lines that are not UTF-8.
+fn lossy_lines(output: &[u8]) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.to_str_lossy().into_owned())
+        .collect()
+}
@@ -0,0 +1,56 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{Distribution, ParsProgram, RunResult, TerminationType};
+
+/// A fixed set of input lines, given to pars with a particular distribution and halt mode.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub distribution: Distribution,
+    pub halt: Option<TerminationType>,
+    pub input: Vec<String>,
+}
+impl Scenario {
+    pub fn new<I, S>(distribution: Distribution, halt: Option<TerminationType>, input: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            distribution,
+            halt,
+            input: input.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Runs the scenario against the binary in `PARS_BINARY` (see [`ParsProgram::from_env`]),
+    /// then passes the result to `check`.
+    ///
+    /// pars is always waited on or killed before this returns.
+    ///
+    /// # Panics
+    /// Panics if pars cannot be started, or if `check` panics. In the latter case the panic
+    /// message is extended with the scenario and everything pars printed to stderr.
+    pub fn run(&self, check: impl FnOnce(&RunResult)) {
+        let mut program = ParsProgram::from_env()
+            .distribution(self.distribution.clone())
+            .halt_opt(self.halt)
+            .spawn()
+            .expect("Failed to start pars");
+        program.run_cmds(&self.input);
+        let result = program.finish();
+
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| check(&result))) {
+            let msg = if let Some(s) = payload.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                panic::resume_unwind(payload);
+            };
+            panic!(
+                "{msg}\n\ndistribution: {:?}\nhalt: {:?}\ninput: {:#?}\npars finished with {}\nstderr: {:#?}",
+                self.distribution, self.halt, self.input, result.status, result.stderr
+            );
+        }
+    }
+}
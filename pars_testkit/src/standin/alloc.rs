@@ -0,0 +1,139 @@
+//! Gives each test its own stand-in, for remotes made with
+//! [`RemoteHost::stand_in`](crate::RemoteHost::stand_in).
+//!
+//! The stand-in is started the first time a thread spawns pars with such a remote, and is
+//! reused for the rest of that thread's runs. Tests run on threads of their own, so each test
+//! gets its own server on its own port, and the stand-in stops when the test's thread ends.
+//!
+//! How many stand-ins may run at once across every thread can be capped with
+//! [`set_stand_in_limit`], in which case a test waits for another to finish before starting
+//! its own. A test the runner gives up on (see [`cancel`](crate::cancel)) stops counting
+//! towards the cap, though its thread, and so its stand-in, may carry on.
+use std::{
+    cell::RefCell,
+    io,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+};
+
+use super::StandIn;
+use crate::cancel::{self, Registration};
+
+/// How many stand-ins are running, and how many may be
+struct Slots {
+    used: usize,
+    max: Option<usize>,
+}
+
+static SLOTS: Mutex<Slots> = Mutex::new(Slots { used: 0, max: None });
+static FREED: Condvar = Condvar::new();
+
+thread_local! {
+    // the stand-in is dropped before its permit is given back
+    static CURRENT: RefCell<Option<(Rc<StandIn>, Permit)>> = const { RefCell::new(None) };
+}
+
+/// Caps how many stand-ins started for [`RemoteHost::stand_in`](crate::RemoteHost::stand_in)
+/// remotes may run at once, across every thread. There is no cap unless one is set.
+pub fn set_stand_in_limit(max: usize) {
+    SLOTS.lock().unwrap().max = Some(max.max(1));
+    FREED.notify_all();
+}
+
+/// A slot for one running stand-in, given back when dropped, or when the test holding it is
+/// cancelled, whichever is first
+struct Permit {
+    held: Arc<AtomicBool>,
+    _cancel: Registration,
+}
+impl Permit {
+    /// Waits for a free slot
+    fn acquire() -> Self {
+        let mut slots = SLOTS.lock().unwrap();
+        while slots.max.is_some_and(|max| slots.used >= max) {
+            slots = FREED.wait(slots).unwrap();
+        }
+        slots.used += 1;
+        drop(slots);
+
+        let held = Arc::new(AtomicBool::new(true));
+        let on_cancel = Arc::clone(&held);
+        Permit {
+            held,
+            _cancel: cancel::on_cancel(move || release(&on_cancel)),
+        }
+    }
+}
+impl Drop for Permit {
+    fn drop(&mut self) {
+        release(&self.held);
+    }
+}
+
+/// Gives back the slot `held` marks, unless it has been already
+fn release(held: &AtomicBool) {
+    if held.swap(false, Ordering::SeqCst) {
+        SLOTS.lock().unwrap().used -= 1;
+        FREED.notify_one();
+    }
+}
+
+/// This thread's stand-in, started with [`StandIn::from_env`] if it has none yet
+pub(crate) fn current() -> io::Result<Rc<StandIn>> {
+    CURRENT.with(|current| {
+        let mut current = current.borrow_mut();
+        if let Some((stand_in, _)) = current.as_ref() {
+            return Ok(Rc::clone(stand_in));
+        }
+
+        let permit = Permit::acquire();
+        let stand_in = Rc::new(StandIn::from_env().start()?);
+        *current = Some((Rc::clone(&stand_in), permit));
+
+        Ok(stand_in)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::mpsc::{self, RecvTimeoutError},
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::cancel::Token;
+
+    #[test]
+    fn test_cancel_gives_back_slot() {
+        set_stand_in_limit(1);
+        let token = Token::new();
+        let entered = token.clone();
+        let (acquired, held) = mpsc::channel();
+        let (_finish, never) = mpsc::channel::<()>();
+        // a test that holds its slot until long after it is given up on
+        thread::spawn(move || {
+            entered.enter();
+            let _permit = Permit::acquire();
+            acquired.send(()).unwrap();
+            let _ = never.recv();
+        });
+        held.recv().unwrap();
+
+        let (acquired, next) = mpsc::channel();
+        thread::spawn(move || {
+            let _permit = Permit::acquire();
+            acquired.send(()).unwrap();
+        });
+        assert_eq!(
+            next.recv_timeout(Duration::from_millis(200)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        token.cancel();
+        assert_eq!(next.recv_timeout(Duration::from_secs(5)), Ok(()));
+    }
+}
@@ -0,0 +1,284 @@
+//! The `ssh` half of the stand-in: a command line compatible enough with OpenSSH's `ssh`
+//! for pars to run it, which talks to a [`StandIn`](super::StandIn) server.
+//!
+//! It is not real ssh, but fails as ssh does, with the same messages and exit status. It never
+//! asks whether to trust a host that is not in `known_hosts`, and refuses it as ssh does when
+//! it has no terminal to ask on. With a terminal, real ssh would ask instead, and what pars
+//! sees then depends on how the prompt is answered.
+use std::{
+    env,
+    ffi::{CStr, OsString},
+    fs,
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::PathBuf,
+    process::ExitCode,
+    thread,
+};
+
+use super::{keys, protocol::Frame};
+
+/// ssh's exit status when it fails itself, rather than the remote command failing
+const SSH_ERROR: u8 = 255;
+
+/// What was asked of `ssh` on its command line
+#[derive(Debug, Default)]
+struct SshArgs {
+    /// The user given as `user@host`, if any
+    user: Option<String>,
+    host: String,
+    port: u16,
+    identity: Option<PathBuf>,
+    /// `-n`: take no input
+    no_stdin: bool,
+    command: Vec<String>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = OsString>) -> Result<SshArgs, String> {
+    let mut args = args.into_iter().map(|a| a.to_string_lossy().into_owned());
+    let mut parsed = SshArgs {
+        port: 22,
+        ..SshArgs::default()
+    };
+    let mut destination = None;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .ok_or(format!("option requires an argument -- {flag}"))
+        };
+        match arg.as_str() {
+            "-p" => {
+                let port = value("p")?;
+                parsed.port = port.parse().map_err(|_| format!("Bad port '{port}'"))?;
+            }
+            "-i" => parsed.identity = Some(PathBuf::from(value("i")?)),
+            // options that take a value this stand-in does not need
+            "-o" | "-l" | "-F" => {
+                value(&arg[1..])?;
+            }
+            "-n" => parsed.no_stdin = true,
+            "--" => {
+                parsed.command.extend(args.by_ref());
+            }
+            flag if flag.starts_with('-') && destination.is_none() => {}
+            _ if destination.is_none() => destination = Some(arg),
+            _ => {
+                parsed.command.push(arg);
+                parsed.command.extend(args.by_ref());
+            }
+        }
+    }
+
+    let destination =
+        destination.ok_or("usage: ssh [-i identity_file] [-p port] destination [command]")?;
+    match destination.rsplit_once('@') {
+        Some((user, host)) => {
+            parsed.user = Some(user.to_string());
+            parsed.host = host.to_string();
+        }
+        None => parsed.host = destination,
+    }
+
+    Ok(parsed)
+}
+
+/// Finds the client's key: the identity file given, or else ssh's default key files
+fn find_key(args: &SshArgs, home: &Option<PathBuf>) -> Option<String> {
+    if let Some(path) = &args.identity {
+        if !path.is_file() {
+            eprintln!(
+                "Warning: Identity file {} not accessible: No such file or directory.",
+                path.display()
+            );
+        }
+        if let Some(key) = keys::read_private_key(path) {
+            return Some(key);
+        }
+    }
+    let ssh_dir = home.as_ref()?.join(".ssh");
+    ["id_ed25519", "id_ecdsa", "id_rsa"]
+        .iter()
+        .find_map(|name| keys::read_private_key(&ssh_dir.join(name)))
+}
+
+/// The name of the user running ssh, which it logs in as unless given another
+fn login_name() -> String {
+    // SAFETY: ssh has no other threads yet, so nothing can overwrite the entry while it is read
+    unsafe {
+        let passwd = libc::getpwuid(libc::getuid());
+        if passwd.is_null() {
+            return "user".to_string();
+        }
+        CStr::from_ptr((*passwd).pw_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Checks the server's key against `~/.ssh/known_hosts`, printing ssh's error if it fails
+fn verify_host(args: &SshArgs, home: &Option<PathBuf>, host_key: &str) -> bool {
+    let known_hosts_path = home
+        .as_ref()
+        .map(|home| home.join(".ssh/known_hosts"))
+        .unwrap_or_default();
+    let known_hosts = fs::read_to_string(&known_hosts_path).unwrap_or_default();
+    let known = keys::known_host_keys(&known_hosts, &args.host, args.port);
+
+    if known.iter().any(|(_, key)| key == host_key) {
+        return true;
+    }
+    // a new host is refused without a word, as ssh does when it cannot ask about it
+    if let Some((line, key)) = known.first() {
+        let name = keys::known_host_name(&args.host, args.port);
+        let key_type = |key: &str| key.split(' ').next().unwrap_or_default().to_string();
+        let file = known_hosts_path.display();
+        eprintln!("@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@");
+        eprintln!("@    WARNING: REMOTE HOST IDENTIFICATION HAS CHANGED!     @");
+        eprintln!("@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@");
+        eprintln!("IT IS POSSIBLE THAT SOMEONE IS DOING SOMETHING NASTY!");
+        eprintln!("Someone could be eavesdropping on you right now (man-in-the-middle attack)!");
+        eprintln!("It is also possible that a host key has just been changed.");
+        eprintln!(
+            "The fingerprint for the {} key sent by the remote host is\n{}.",
+            key_type(host_key),
+            keys::fingerprint(host_key)
+        );
+        eprintln!("Please contact your system administrator.");
+        eprintln!("Add correct host key in {file} to get rid of this message.");
+        eprintln!("Offending {} key in {file}:{line}", key_type(key));
+        eprintln!("  remove with:");
+        eprintln!("  ssh-keygen -f \"{file}\" -R \"{name}\"");
+        eprintln!("Host key for {name} has changed and you have requested strict checking.");
+    }
+    eprintln!("Host key verification failed.");
+
+    false
+}
+
+/// Copies the client's stdin to the server until it ends
+fn send_stdin(mut stream: TcpStream, no_stdin: bool) {
+    if !no_stdin {
+        let mut stdin = io::stdin().lock();
+        let mut buf = [0; 8192];
+        loop {
+            match stdin.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if Frame::Stdin(buf[..n].to_vec())
+                        .write_to(&mut stream)
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+    let _ = Frame::StdinEof.write_to(&mut stream);
+}
+
+/// Runs as `ssh`, taking its arguments from the command line.
+///
+/// Connects to the stand-in server at the given host and port, checks its host key, offers
+/// the client's key, and then runs the command, passing input and output through. Exits
+/// with the command's exit status, or 255 if ssh itself failed, as OpenSSH does.
+pub fn ssh_main() -> ExitCode {
+    let args = match parse_args(env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(SSH_ERROR);
+        }
+    };
+    match run(&args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("ssh: {e}");
+            ExitCode::from(SSH_ERROR)
+        }
+    }
+}
+
+fn run(args: &SshArgs) -> io::Result<ExitCode> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let mut stream = match TcpStream::connect((args.host.as_str(), args.port)) {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("ssh: connect to host {} port {}: {e}", args.host, args.port);
+            return Ok(ExitCode::from(SSH_ERROR));
+        }
+    };
+    let closed = || {
+        eprintln!("Connection to {} closed by remote host.", args.host);
+        Ok(ExitCode::from(SSH_ERROR))
+    };
+
+    let Some(Frame::HostKey(host_key)) = Frame::read_from(&mut stream)? else {
+        return closed();
+    };
+    if !verify_host(args, &home, &host_key) {
+        return Ok(ExitCode::from(SSH_ERROR));
+    }
+
+    Frame::Auth(find_key(args, &home).unwrap_or_default()).write_to(&mut stream)?;
+    match Frame::read_from(&mut stream)? {
+        Some(Frame::AuthOk) => {}
+        Some(Frame::AuthFailed) => {
+            let user = args.user.clone().unwrap_or_else(login_name);
+            eprintln!("{user}@{}: Permission denied (publickey).", args.host);
+            return Ok(ExitCode::from(SSH_ERROR));
+        }
+        _ => return closed(),
+    }
+
+    // ssh joins the command's arguments with spaces, for the remote shell to split again
+    Frame::Exec(args.command.join(" ")).write_to(&mut stream)?;
+    let input = stream.try_clone()?;
+    let no_stdin = args.no_stdin;
+    thread::spawn(move || send_stdin(input, no_stdin));
+
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr().lock();
+    loop {
+        match Frame::read_from(&mut stream) {
+            Ok(Some(Frame::Stdout(data))) => {
+                stdout.write_all(&data)?;
+                stdout.flush()?;
+            }
+            Ok(Some(Frame::Stderr(data))) => {
+                stderr.write_all(&data)?;
+                stderr.flush()?;
+            }
+            Ok(Some(Frame::Exit(code))) => {
+                return Ok(ExitCode::from(u8::try_from(code).unwrap_or(SSH_ERROR)));
+            }
+            Ok(_) | Err(_) => return closed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pars_libs_args() {
+        // as built by `pars_libs::RemoteCommand`
+        let args = ["-p", "1234", "localhost", "-i", "/key", "--", "echo", "a b"];
+        let args = parse_args(args.map(OsString::from)).unwrap();
+        assert_eq!(args.host, "localhost");
+        assert_eq!(args.port, 1234);
+        assert_eq!(args.identity, Some(PathBuf::from("/key")));
+        assert_eq!(args.command, ["echo", "a b"]);
+    }
+
+    #[test]
+    fn test_parse_command_without_separator() {
+        let args = ["user@host", "ls", "-l"];
+        let args = parse_args(args.map(OsString::from)).unwrap();
+        assert_eq!(args.user.as_deref(), Some("user"));
+        assert_eq!(args.host, "host");
+        assert_eq!(args.port, 22);
+        assert_eq!(args.command, ["ls", "-l"]);
+    }
+}
@@ -0,0 +1,369 @@
+//! A hermetic stand-in for an ssh server, so remote runs can be tested without a real one.
+//!
+//! pars reaches a remote by running `ssh -p <port> <host> [-i <key>] -- <command>`. A
+//! [`StandIn`] serves those connections on a free local port, and sets up a throwaway home
+//! directory and `PATH` for pars (see [`StandIn::setup`]):
+//! - `~/.ssh/cs6991/cs6991-id` holds the client key, where pars looks for it.
+//! - `~/.ssh/known_hosts` lists the server's host key.
+//! - `ssh` on the `PATH` is the stand-in's client, which is whatever executable calls
+//!   [`ssh_main`] when it is run under the name `ssh`.
+//!
+//! Each part can be set up wrongly instead, to test how pars deals with a missing or wrong key,
+//! or an unknown host. Commands run with `sh -c` in the home directory, with only `HOME`,
+//! `PATH` and the variables below set, and `pars` on the `PATH` if a pars binary was given.
+//!
+//! The server can also break up the output it sends (see [`StandInBuilder::fragment`]), to
+//! test how pars puts it back together, and listen on several ports, each a separate remote
+//! to pars (see [`StandInBuilder::ports`]). Commands are given the path of an execution log
+//! for their remote in `STANDIN_LOG`, to record what ran where. Connections can be dropped at
+//! chosen points (see [`Disconnect`]).
+//!
+//! Rather than starting one by hand, a test can use [`RemoteHost::stand_in`] remotes, which
+//! are run on a stand-in of the test's own when pars is spawned (see [`set_stand_in_limit`]).
+mod alloc;
+mod client;
+pub(crate) mod keys;
+pub(crate) mod protocol;
+mod server;
+
+use std::{
+    env,
+    ffi::OsString,
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+pub(crate) use alloc::current;
+pub use alloc::set_stand_in_limit;
+pub use client::ssh_main;
+
+use crate::{ParsBuilder, RemoteHost, TempDir, BINARY_VAR};
+
+/// The address the stand-in listens on
+const HOST: &str = "127.0.0.1";
+
+/// Environment variable holding a path for commands run on a stand-in to create, to ask for
+/// their connection to be dropped (see [`Disconnect::DuringLine`])
+pub const STAND_IN_DISCONNECT_VAR: &str = "STANDIN_DISCONNECT";
+
+/// Environment variable holding the path of the execution log (see [`StandIn::log`]) for
+/// commands run on a stand-in
+pub const STAND_IN_LOG_VAR: &str = "STANDIN_LOG";
+
+/// Which key the client is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientKey {
+    /// A key the server accepts
+    #[default]
+    Authorized,
+    /// A key the server does not accept
+    Unauthorized,
+    /// No key at all
+    Missing,
+}
+
+/// What the client's `known_hosts` says about the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KnownHost {
+    /// The server's host key
+    #[default]
+    Known,
+    /// Nothing, as if it had never been connected to. The client refuses the host, as ssh does
+    /// when it has no terminal to ask whether to trust it on.
+    Unknown,
+    /// A different host key, as if the server had been replaced
+    Changed,
+}
+
+/// When the server drops a connection, as if the network had gone away.
+///
+/// Each drop is written to the execution log of its port as `disconnect <when> <nanoseconds
+/// since the epoch>`, where `<when>` is [`Disconnect::as_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disconnect {
+    /// As soon as the connection is made, before ssh has authenticated
+    AtStartup,
+    /// While the command is running, once it has created the file named in
+    /// `STANDIN_DISCONNECT`. The command is killed.
+    DuringLine,
+    /// Once the command has finished and its output has been sent, but before its exit
+    /// status. For a pars that runs one session per line, this is between lines.
+    BetweenLines,
+}
+impl Disconnect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Disconnect::AtStartup => "at_startup",
+            Disconnect::DuringLine => "during_line",
+            Disconnect::BetweenLines => "between_lines",
+        }
+    }
+}
+
+/// Configures and starts a [`StandIn`].
+///
+/// Created with [`StandIn::builder`].
+#[derive(Debug)]
+pub struct StandInBuilder {
+    client: PathBuf,
+    pars: Option<PathBuf>,
+    client_key: ClientKey,
+    known_host: KnownHost,
+    fragment: Option<usize>,
+    ports: usize,
+    disconnects: Vec<(usize, Disconnect)>,
+}
+impl StandInBuilder {
+    /// Puts this pars binary on the `PATH` of remote commands, as `pars`
+    pub fn pars(mut self, pars: impl Into<PathBuf>) -> Self {
+        self.pars = Some(pars.into());
+        self
+    }
+
+    /// Sets which key the client has
+    pub fn client_key(mut self, key: ClientKey) -> Self {
+        self.client_key = key;
+        self
+    }
+
+    /// Sets what the client's `known_hosts` says about the server
+    pub fn known_host(mut self, known: KnownHost) -> Self {
+        self.known_host = known;
+        self
+    }
+
+    /// Sends the output of commands in pieces of at most `max_bytes`, varying in size, each
+    /// written to the connection on its own, so lines reach pars split at arbitrary points.
+    pub fn fragment(mut self, max_bytes: usize) -> Self {
+        self.fragment = Some(max_bytes.max(1));
+        self
+    }
+
+    /// Listens on `ports` ports rather than one, each of which is a separate remote to pars
+    /// (see [`StandIn::remote_on`]) with its own execution log.
+    pub fn ports(mut self, ports: usize) -> Self {
+        self.ports = ports.max(1);
+        self
+    }
+
+    /// Drops a connection to the `port`th port at `when`. Each call drops one connection, the
+    /// first to reach that point; later connections are served as normal.
+    pub fn disconnect(mut self, port: usize, when: Disconnect) -> Self {
+        self.disconnects.push((port, when));
+        self
+    }
+
+    /// Starts serving on free ports.
+    pub fn start(self) -> io::Result<StandIn> {
+        let dir = TempDir::new("pars-standin")?;
+        let home = dir.join("home");
+        let bin = dir.join("bin");
+        let remote_bin = dir.join("remote-bin");
+        for path in [&home.join(".ssh"), &bin, &remote_bin] {
+            std::fs::create_dir_all(path)?;
+        }
+        symlink(&self.client, bin.join("ssh"))?;
+        if let Some(pars) = &self.pars {
+            symlink(pars, remote_bin.join("pars"))?;
+        }
+
+        let listeners = (0..self.ports)
+            .map(|_| TcpListener::bind((HOST, 0)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let addrs = listeners
+            .iter()
+            .map(TcpListener::local_addr)
+            .collect::<io::Result<Vec<_>>>()?;
+        let logs: Vec<_> = (0..addrs.len())
+            .map(|i| dir.join(format!("remote-{i}.log")))
+            .collect();
+
+        let host_key = keys::generate("host");
+        let client_key = keys::generate("client");
+        let key_path = home.join(".ssh/cs6991/cs6991-id");
+        match self.client_key {
+            ClientKey::Authorized => keys::write_private_key(&key_path, &client_key)?,
+            ClientKey::Unauthorized => {
+                keys::write_private_key(&key_path, &keys::generate("client"))?
+            }
+            ClientKey::Missing => {}
+        }
+        let known_key = match self.known_host {
+            KnownHost::Known => Some(host_key.clone()),
+            KnownHost::Unknown => None,
+            KnownHost::Changed => Some(keys::generate("host")),
+        };
+        let known_hosts = known_key
+            .map(|key| {
+                let mut known_hosts = String::new();
+                for addr in &addrs {
+                    for host in ["127.0.0.1", "localhost"] {
+                        let name = keys::known_host_name(host, addr.port());
+                        known_hosts.push_str(&format!("{name} {key}\n"));
+                    }
+                }
+                known_hosts
+            })
+            .unwrap_or_default();
+        std::fs::write(home.join(".ssh/known_hosts"), known_hosts)?;
+
+        let shared = Arc::new(server::Shared {
+            host_key,
+            authorized: vec![client_key],
+            home: home.clone(),
+            path: prepend_path(&remote_bin),
+            fragment: self.fragment,
+        });
+        let stop = Arc::new(AtomicBool::new(false));
+        let triggers = dir.join("disconnect");
+        std::fs::create_dir(&triggers)?;
+        let handles = listeners
+            .into_iter()
+            .enumerate()
+            .map(|(i, listener)| {
+                let port = Arc::new(server::Port {
+                    log: logs[i].clone(),
+                    triggers: triggers.join(i.to_string()),
+                    disconnects: Mutex::new(
+                        self.disconnects
+                            .iter()
+                            .filter(|&&(port, _)| port == i)
+                            .map(|&(_, when)| when)
+                            .collect(),
+                    ),
+                    sessions: AtomicUsize::new(0),
+                });
+                std::fs::create_dir(&port.triggers)?;
+                let shared = Arc::clone(&shared);
+                let stop = Arc::clone(&stop);
+                Ok(thread::spawn(move || {
+                    server::serve(listener, shared, port, stop)
+                }))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(StandIn {
+            addrs,
+            logs,
+            _dir: dir,
+            home,
+            bin,
+            stop,
+            handles,
+        })
+    }
+}
+
+/// `PATH`, with `dir` searched first
+fn prepend_path(dir: &Path) -> OsString {
+    let mut paths = vec![dir.to_path_buf()];
+    if let Some(path) = env::var_os("PATH") {
+        paths.extend(env::split_paths(&path));
+    }
+    env::join_paths(paths).unwrap_or_default()
+}
+
+/// A running stand-in ssh server. It stops when dropped.
+#[derive(Debug)]
+pub struct StandIn {
+    addrs: Vec<SocketAddr>,
+    logs: Vec<PathBuf>,
+    // keeps the home directory and executables until the stand-in is dropped
+    _dir: TempDir,
+    home: PathBuf,
+    bin: PathBuf,
+    stop: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+impl StandIn {
+    /// Starts configuring a stand-in. `client` is an executable that calls [`ssh_main`] when
+    /// run as `ssh`, such as the test runner itself.
+    pub fn builder(client: impl Into<PathBuf>) -> StandInBuilder {
+        StandInBuilder {
+            client: client.into(),
+            pars: None,
+            client_key: ClientKey::default(),
+            known_host: KnownHost::default(),
+            fragment: None,
+            ports: 1,
+            disconnects: Vec::new(),
+        }
+    }
+
+    /// Starts configuring a stand-in whose client is the current executable, with the pars
+    /// executable named by the `PARS_BINARY` environment variable, if it is set, as `pars`.
+    ///
+    /// # Panics
+    /// Panics if the current executable cannot be found.
+    pub fn from_env() -> StandInBuilder {
+        let client = env::current_exe().expect("Failed to find the current executable");
+        let builder = Self::builder(client);
+        match env::var_os(BINARY_VAR) {
+            Some(pars) => builder.pars(pars),
+            None => builder,
+        }
+    }
+
+    /// The address the server is listening on, or the first of them if it has several ports
+    pub fn addr(&self) -> SocketAddr {
+        self.addrs[0]
+    }
+
+    /// The server as a remote for pars, running `threads` lines at once
+    pub fn remote(&self, threads: u32) -> RemoteHost {
+        self.remote_on(0, threads)
+    }
+
+    /// The remote on the `port`th of the server's ports (see [`StandInBuilder::ports`]),
+    /// running `threads` lines at once
+    ///
+    /// # Panics
+    /// Panics if the server has no such port.
+    pub fn remote_on(&self, port: usize, threads: u32) -> RemoteHost {
+        RemoteHost::new(HOST, self.addrs[port].port(), threads)
+    }
+
+    /// The execution log of the remote on the `port`th port.
+    ///
+    /// The file is only written by remote commands themselves, which are given its path in
+    /// [`STAND_IN_LOG_VAR`], so they can record what ran on which remote, and when. It does not exist
+    /// until a command writes to it.
+    ///
+    /// # Panics
+    /// Panics if the server has no such port.
+    pub fn log(&self, port: usize) -> &Path {
+        &self.logs[port]
+    }
+
+    /// The home directory given to pars, which is also where remote commands run
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Points pars at this stand-in, by giving it the stand-in's home directory and putting
+    /// the stand-in's `ssh` first on its `PATH`.
+    pub fn setup(&self, builder: ParsBuilder) -> ParsBuilder {
+        builder
+            .env("HOME", &self.home)
+            .env("PATH", prepend_path(&self.bin))
+    }
+}
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake each listener from accepting, so it sees it has been stopped
+        for addr in &self.addrs {
+            let _ = TcpStream::connect(addr);
+        }
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
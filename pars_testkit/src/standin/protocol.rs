@@ -0,0 +1,135 @@
+//! The messages passed between the stand-in's `ssh` client and its server.
+//!
+//! Each frame is a one byte tag, then the length of the payload as a big endian `u32`, then
+//! the payload itself.
+use std::io::{self, Read, Write};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Frame {
+    /// server: the key identifying the server, checked against `known_hosts`
+    HostKey(String),
+    /// client: the key the client has, or an empty string if it has none
+    Auth(String),
+    /// server: the client's key is authorized
+    AuthOk,
+    /// server: the client's key is not authorized, and the connection will be closed
+    AuthFailed,
+    /// client: the command line to run
+    Exec(String),
+    /// client: input for the command
+    Stdin(Vec<u8>),
+    /// client: the command's input has ended
+    StdinEof,
+    /// server: output from the command
+    Stdout(Vec<u8>),
+    /// server: error output from the command
+    Stderr(Vec<u8>),
+    /// server: the command's exit status, after all of its output
+    Exit(i32),
+}
+impl Frame {
+    fn encode(&self) -> (u8, Vec<u8>) {
+        match self {
+            Frame::HostKey(key) => (b'H', key.as_bytes().to_vec()),
+            Frame::Auth(key) => (b'A', key.as_bytes().to_vec()),
+            Frame::AuthOk => (b'O', Vec::new()),
+            Frame::AuthFailed => (b'F', Vec::new()),
+            Frame::Exec(command) => (b'E', command.as_bytes().to_vec()),
+            Frame::Stdin(data) => (b'I', data.clone()),
+            Frame::StdinEof => (b'i', Vec::new()),
+            Frame::Stdout(data) => (b'1', data.clone()),
+            Frame::Stderr(data) => (b'2', data.clone()),
+            Frame::Exit(code) => (b'X', code.to_be_bytes().to_vec()),
+        }
+    }
+
+    fn decode(tag: u8, payload: Vec<u8>) -> io::Result<Self> {
+        let text = |payload: Vec<u8>| {
+            String::from_utf8(payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        };
+        Ok(match tag {
+            b'H' => Frame::HostKey(text(payload)?),
+            b'A' => Frame::Auth(text(payload)?),
+            b'O' => Frame::AuthOk,
+            b'F' => Frame::AuthFailed,
+            b'E' => Frame::Exec(text(payload)?),
+            b'I' => Frame::Stdin(payload),
+            b'i' => Frame::StdinEof,
+            b'1' => Frame::Stdout(payload),
+            b'2' => Frame::Stderr(payload),
+            b'X' => {
+                let code = payload.try_into().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "exit status is not 4 bytes")
+                })?;
+                Frame::Exit(i32::from_be_bytes(code))
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown frame tag {tag:#04x}"),
+                ))
+            }
+        })
+    }
+
+    /// Whether a frame can start with `tag`, to tell the stand-in's messages from other
+    /// bytes before reading a length from them
+    pub fn is_tag(tag: u8) -> bool {
+        b"HAOFEIi12X".contains(&tag)
+    }
+
+    /// The frame as it is sent on the wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (tag, payload) = self.encode();
+        let mut bytes = Vec::with_capacity(5 + payload.len());
+        bytes.push(tag);
+        bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&payload);
+
+        bytes
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())?;
+        writer.flush()
+    }
+
+    /// Reads the next frame, or `None` if the stream ended cleanly between frames
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut tag = [0; 1];
+        if reader.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let mut payload = vec![0; u32::from_be_bytes(len) as usize];
+        reader.read_exact(&mut payload)?;
+
+        Self::decode(tag[0], payload).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frames = [
+            Frame::HostKey("host".to_string()),
+            Frame::Auth(String::new()),
+            Frame::AuthOk,
+            Frame::Exec("echo hi".to_string()),
+            Frame::Stdout(b"hi\n".to_vec()),
+            Frame::StdinEof,
+            Frame::Exit(-1),
+        ];
+        let bytes: Vec<u8> = frames.iter().flat_map(Frame::to_bytes).collect();
+
+        let mut reader = bytes.as_slice();
+        for frame in &frames {
+            assert_eq!(Frame::read_from(&mut reader).unwrap().as_ref(), Some(frame));
+        }
+        assert_eq!(Frame::read_from(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! The server half of the stand-in, which authenticates clients and runs their commands.
+use std::{
+    ffi::OsString,
+    fs::OpenOptions,
+    io::{self, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    os::unix::process::CommandExt,
+    path::PathBuf,
+    process::{ChildStdin, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{protocol::Frame, Disconnect, STAND_IN_DISCONNECT_VAR, STAND_IN_LOG_VAR};
+
+/// How long to wait after sending each piece of fragmented output
+const FRAGMENT_PAUSE: Duration = Duration::from_micros(200);
+
+/// How often to check whether a command has asked to be disconnected
+const DISCONNECT_POLL: Duration = Duration::from_millis(10);
+
+/// What every session of a server shares.
+#[derive(Debug)]
+pub(crate) struct Shared {
+    pub host_key: String,
+    pub authorized: Vec<String>,
+    /// Where commands run, and their `HOME`
+    pub home: PathBuf,
+    /// `PATH` for commands
+    pub path: OsString,
+    /// The largest piece of output to send at once, if output is being fragmented
+    pub fragment: Option<usize>,
+}
+
+/// What the sessions on one of a server's ports share.
+#[derive(Debug)]
+pub(crate) struct Port {
+    /// The execution log given to commands
+    pub log: PathBuf,
+    /// Where commands are given files to create to ask to be disconnected
+    pub triggers: PathBuf,
+    /// Connections still to be dropped, and when
+    pub disconnects: Mutex<Vec<Disconnect>>,
+    /// Sessions started so far
+    pub sessions: AtomicUsize,
+}
+impl Port {
+    /// Whether a connection is still to be dropped at `when`
+    fn pending(&self, when: Disconnect) -> bool {
+        self.disconnects.lock().unwrap().contains(&when)
+    }
+
+    /// Uses up a drop at `when`, if there is one left, logging it to the execution log
+    fn take(&self, when: Disconnect) -> bool {
+        let mut disconnects = self.disconnects.lock().unwrap();
+        let Some(i) = disconnects.iter().position(|&d| d == when) else {
+            return false;
+        };
+        disconnects.remove(i);
+
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let entry = format!("disconnect {} {at}\n", when.as_str());
+        // the log is only for tests to read, so failing to write it is not fatal
+        let _ = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log)
+            .and_then(|mut log| log.write_all(entry.as_bytes()));
+
+        true
+    }
+}
+
+/// Accepts connections until `stop` is set, running each session on its own thread.
+pub(crate) fn serve(
+    listener: TcpListener,
+    shared: Arc<Shared>,
+    port: Arc<Port>,
+    stop: Arc<AtomicBool>,
+) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let shared = Arc::clone(&shared);
+        let port = Arc::clone(&port);
+        thread::spawn(move || {
+            // a session ends early whenever the client goes away, which is not an error here
+            let _ = session(stream, &shared, &port);
+        });
+    }
+}
+
+/// Sends a frame on a connection shared between threads
+fn send(stream: &Mutex<TcpStream>, frame: &Frame) -> io::Result<()> {
+    frame.write_to(&mut *stream.lock().unwrap())
+}
+
+fn session(stream: TcpStream, shared: &Shared, port: &Arc<Port>) -> io::Result<()> {
+    if port.take(Disconnect::AtStartup) {
+        return Ok(());
+    }
+    let trigger = port.triggers.join(format!(
+        "session-{}",
+        port.sessions.fetch_add(1, Ordering::Relaxed)
+    ));
+    // each piece of fragmented output should go out in a segment of its own
+    stream.set_nodelay(shared.fragment.is_some())?;
+    let mut reader = stream.try_clone()?;
+    let writer = Arc::new(Mutex::new(stream));
+
+    send(&writer, &Frame::HostKey(shared.host_key.clone()))?;
+    let Some(Frame::Auth(key)) = Frame::read_from(&mut reader)? else {
+        return Ok(());
+    };
+    if !shared.authorized.contains(&key) {
+        return send(&writer, &Frame::AuthFailed);
+    }
+    send(&writer, &Frame::AuthOk)?;
+    let Some(Frame::Exec(command)) = Frame::read_from(&mut reader)? else {
+        return Ok(());
+    };
+
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .current_dir(&shared.home)
+        // like sshd, which passes on nothing of the client's environment
+        .env_clear()
+        .env("HOME", &shared.home)
+        .env("PATH", &shared.path)
+        .env(STAND_IN_LOG_VAR, &port.log)
+        .env(STAND_IN_DISCONNECT_VAR, &trigger)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // in a group of its own, so everything it starts can be killed together
+        .process_group(0)
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            send(&writer, &Frame::Stderr(format!("sh: {e}\n").into_bytes()))?;
+            return send(&writer, &Frame::Exit(127));
+        }
+    };
+    let pid = child.id() as libc::pid_t;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| forward(out, &writer, shared.fragment, Frame::Stdout));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| forward(err, &writer, shared.fragment, Frame::Stderr));
+    let finished = Arc::new(AtomicBool::new(false));
+    thread::spawn({
+        let stdin = child.stdin.take();
+        let finished = Arc::clone(&finished);
+        move || {
+            receive_input(reader, stdin);
+            // the client has gone, so nothing is left to see the command's output
+            if !finished.load(Ordering::Relaxed) {
+                // SAFETY: kill has no memory safety requirements
+                unsafe { libc::kill(-pid, libc::SIGKILL) };
+            }
+        }
+    });
+
+    if port.pending(Disconnect::DuringLine) {
+        let finished = Arc::clone(&finished);
+        let port = Arc::clone(port);
+        let stream = writer.lock().unwrap().try_clone()?;
+        thread::spawn(move || {
+            while !finished.load(Ordering::Relaxed) {
+                if trigger.exists() && port.take(Disconnect::DuringLine) {
+                    // the client sees the connection close, and input stops, killing the command
+                    let _ = stream.shutdown(Shutdown::Both);
+                    return;
+                }
+                thread::sleep(DISCONNECT_POLL);
+            }
+        });
+    }
+
+    let status = child.wait()?;
+    finished.store(true, Ordering::Relaxed);
+    for forwarder in stdout.into_iter().chain(stderr) {
+        let _ = forwarder.join();
+    }
+    if port.take(Disconnect::BetweenLines) {
+        // the command's output has been sent, but not its exit status
+        return writer.lock().unwrap().shutdown(Shutdown::Both);
+    }
+    // like sshd, report a command killed by a signal as failing
+    send(&writer, &Frame::Exit(status.code().unwrap_or(255)))
+}
+
+/// Sends everything read from a command's output to the client, as frames made by `frame`.
+///
+/// If `fragment` is given, each read is sent in pieces of 1 up to that many bytes, with a
+/// pause after each so they are not joined up again on the way.
+fn forward(
+    mut output: impl Read + Send + 'static,
+    writer: &Arc<Mutex<TcpStream>>,
+    fragment: Option<usize>,
+    frame: fn(Vec<u8>) -> Frame,
+) -> thread::JoinHandle<()> {
+    let writer = Arc::clone(writer);
+    thread::spawn(move || {
+        let mut buf = [0; 8192];
+        // piece sizes cycle through 1..=fragment, so lines are split at every offset
+        let mut sizes = (1..=fragment.unwrap_or(1)).cycle();
+        loop {
+            let n = match output.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let mut data = &buf[..n];
+            while !data.is_empty() {
+                let size = fragment.map_or(data.len(), |_| sizes.next().unwrap_or(1));
+                let (piece, rest) = data.split_at(size.min(data.len()));
+                if send(&writer, &frame(piece.to_vec())).is_err() {
+                    return;
+                }
+                if fragment.is_some() {
+                    thread::sleep(FRAGMENT_PAUSE);
+                }
+                data = rest;
+            }
+        }
+    })
+}
+
+/// Passes input from the client to the command, until the client disconnects
+fn receive_input(mut reader: TcpStream, mut stdin: Option<ChildStdin>) {
+    loop {
+        match Frame::read_from(&mut reader) {
+            Ok(Some(Frame::Stdin(data))) => {
+                if let Some(input) = stdin.as_mut() {
+                    // the command has stopped reading, so drop the rest
+                    if input.write_all(&data).is_err() {
+                        stdin = None;
+                    }
+                }
+            }
+            Ok(Some(Frame::StdinEof)) => stdin = None,
+            Ok(Some(_)) => {}
+            Ok(None) | Err(_) => return,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt, str::FromStr};
+
+/// The halt mode given to pars with `--halt`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TerminationType {
+    Never,
+    Lazy,
+    Eager,
+}
+impl TerminationType {
+    /// All halt modes, in order of how quickly pars stops after an error
+    pub const ALL: [TerminationType; 3] = [Self::Never, Self::Lazy, Self::Eager];
+
+    /// The value given to `--halt`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Lazy => "lazy",
+            Self::Eager => "eager",
+        }
+    }
+}
+impl FromStr for TerminationType {
+    type Err = fmt::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use TerminationType::*;
+        match s {
+            "never" => Ok(Never),
+            "lazy" => Ok(Lazy),
+            "eager" => Ok(Eager),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+impl fmt::Debug for TerminationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
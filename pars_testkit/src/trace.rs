@@ -0,0 +1,417 @@
+//! Records what pars sends to its remotes, and what they send back, to debug remote runs after
+//! the fact.
+//!
+//! A run traced with [`ParsBuilder::trace`](crate::ParsBuilder::trace) has a transparent TCP
+//! proxy put in front of each of its remotes, and pars is pointed at the proxies instead. Each
+//! proxy passes bytes through untouched, and appends them to the trace file as they arrive,
+//! one line per read:
+//!
+//! ```text
+//! # run at <nanoseconds since the epoch>
+//! # r0 127.0.0.1:40123 via 127.0.0.1:40125
+//! 0.000412 r0 c0 open
+//! 0.000950 r0 c0 < 48000000...
+//! 0.001204 r0 c0 > eof
+//! ```
+//!
+//! Each line is the seconds since the run started, the remote, the connection, and then
+//! either an event, or a direction (`>` towards the remote, `<` back from it) followed by the
+//! bytes in hex or `eof`. Several runs can share a file, each starting with its own header.
+//! [`pretty`] splits the bytes back into the stand-in's messages.
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::{self, ErrorKind, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bstr::BStr;
+
+use crate::{
+    standin::{keys, protocol::Frame},
+    RemoteHost,
+};
+
+/// The address the proxies listen on
+const HOST: &str = "127.0.0.1";
+
+/// The name of the trace file for the test `test`, e.g. `test_2_1.test_remote_r1.trace` for
+/// `test_2_1::test_remote/r1`
+pub(crate) fn file_name(test: &str) -> String {
+    let name: String = test
+        .replace("::", ".")
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    format!("{name}.trace")
+}
+
+/// The trace file of a run, shared by its proxies
+struct Recorder {
+    file: Mutex<File>,
+    started: Instant,
+    connections: AtomicUsize,
+}
+impl Recorder {
+    fn record(&self, remote: usize, conn: usize, event: &str) {
+        // timed under the lock, so the file stays in order
+        let mut file = self.file.lock().unwrap();
+        let line = format!(
+            "{:.6} r{remote} c{conn} {event}\n",
+            self.started.elapsed().as_secs_f64()
+        );
+        // a trace is only a debugging aid, so failing to write one does not fail the run
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Proxies recording the connections to each remote of a run. They stop when dropped.
+pub(crate) struct Tracer {
+    /// Each remote as it was given, and the address of its proxy
+    proxies: Vec<(RemoteHost, SocketAddr)>,
+    stop: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+impl Tracer {
+    /// Starts a proxy in front of each of `remotes`, appending to the trace file at `path`,
+    /// and points each remote at its proxy.
+    pub fn start(path: &Path, remotes: &mut [RemoteHost]) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut header = format!("# run at {}\n", since_epoch.as_nanos());
+
+        let listeners = remotes
+            .iter()
+            .map(|_| TcpListener::bind((HOST, 0)))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut proxies = Vec::new();
+        for (i, (remote, listener)) in remotes.iter_mut().zip(&listeners).enumerate() {
+            let addr = listener.local_addr()?;
+            header.push_str(&format!(
+                "# r{i} {}:{} via {addr}\n",
+                remote.remote.addr, remote.remote.port
+            ));
+            let proxied = RemoteHost::new(HOST, addr.port(), remote.threads);
+            proxies.push((std::mem::replace(remote, proxied), addr));
+        }
+        file.write_all(header.as_bytes())?;
+
+        let recorder = Arc::new(Recorder {
+            file: Mutex::new(file),
+            started: Instant::now(),
+            connections: AtomicUsize::new(0),
+        });
+        let stop = Arc::new(AtomicBool::new(false));
+        let handles = listeners
+            .into_iter()
+            .zip(&proxies)
+            .enumerate()
+            .map(|(i, (listener, (remote, _)))| {
+                let target = (remote.remote.addr.clone(), remote.remote.port);
+                let recorder = Arc::clone(&recorder);
+                let stop = Arc::clone(&stop);
+                thread::spawn(move || serve(listener, target, i, recorder, stop))
+            })
+            .collect();
+
+        Ok(Self {
+            proxies,
+            stop,
+            handles,
+        })
+    }
+
+    /// Has ssh trust each proxy as it does the remote behind it, by listing the remote's keys
+    /// for the proxy's address in the `known_hosts` of `home`, if it has one.
+    pub fn trust(&self, home: &Path) -> io::Result<()> {
+        let path = home.join(".ssh/known_hosts");
+        let Ok(mut known_hosts) = fs::read_to_string(&path) else {
+            return Ok(());
+        };
+        let mut added = String::new();
+        for (remote, addr) in &self.proxies {
+            let name = keys::known_host_name(HOST, addr.port());
+            for (_, key) in
+                keys::known_host_keys(&known_hosts, &remote.remote.addr, remote.remote.port)
+            {
+                added.push_str(&format!("{name} {key}\n"));
+            }
+        }
+        known_hosts.push_str(&added);
+
+        fs::write(path, known_hosts)
+    }
+}
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake each proxy from accepting, so it sees it has been stopped
+        for (_, addr) in &self.proxies {
+            let _ = TcpStream::connect(addr);
+        }
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Accepts connections until `stop` is set, connecting each to `target` and recording what
+/// passes between them as the `remote`th remote.
+fn serve(
+    listener: TcpListener,
+    target: (String, u16),
+    remote: usize,
+    recorder: Arc<Recorder>,
+    stop: Arc<AtomicBool>,
+) {
+    for client in listener.incoming() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(client) = client else {
+            continue;
+        };
+        let conn = recorder.connections.fetch_add(1, Ordering::Relaxed);
+        recorder.record(remote, conn, "open");
+        let server = match TcpStream::connect((target.0.as_str(), target.1)) {
+            Ok(server) => server,
+            Err(e) => {
+                // dropping the client closes it, much as the remote refusing it would
+                recorder.record(remote, conn, &format!("error {e}"));
+                continue;
+            }
+        };
+        let (Ok(client_reader), Ok(server_reader)) = (client.try_clone(), server.try_clone())
+        else {
+            continue;
+        };
+
+        let to_remote = Arc::clone(&recorder);
+        thread::spawn(move || pipe(client_reader, server, '>', (remote, conn), &to_remote));
+        let from_remote = Arc::clone(&recorder);
+        thread::spawn(move || pipe(server_reader, client, '<', (remote, conn), &from_remote));
+    }
+}
+
+/// Copies `from` to `to` until `from` ends, recording each read, then passes the end on
+fn pipe(
+    mut from: TcpStream,
+    mut to: TcpStream,
+    direction: char,
+    (remote, conn): (usize, usize),
+    recorder: &Recorder,
+) {
+    let mut buf = [0; 8192];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let hex: String = buf[..n].iter().map(|b| format!("{b:02x}")).collect();
+                recorder.record(remote, conn, &format!("{direction} {hex}"));
+                if to.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    recorder.record(remote, conn, &format!("{direction} eof"));
+    let _ = to.shutdown(Shutdown::Write);
+}
+
+/// What has arrived so far in one direction of a connection
+#[derive(Default)]
+struct Stream {
+    buf: Vec<u8>,
+    /// The bytes are not the stand-in's messages, so are shown as they arrived
+    raw: bool,
+}
+impl Stream {
+    /// Takes every message that has fully arrived
+    fn messages(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        while !self.buf.is_empty() {
+            if self.raw || !Frame::is_tag(self.buf[0]) {
+                self.raw = true;
+                messages.push(format!("raw {:?}", BStr::new(&self.buf)));
+                self.buf.clear();
+                break;
+            }
+
+            let mut reader = self.buf.as_slice();
+            match Frame::read_from(&mut reader) {
+                Ok(Some(frame)) => {
+                    let used = self.buf.len() - reader.len();
+                    self.buf.drain(..used);
+                    messages.push(describe(&frame));
+                }
+                // the rest of the message is still to come
+                Ok(None) => break,
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(_) => self.raw = true,
+            }
+        }
+
+        messages
+    }
+}
+
+/// A message as shown by [`pretty`], e.g. `Stdout "hi\n"`
+fn describe(frame: &Frame) -> String {
+    match frame {
+        Frame::HostKey(key) => format!("HostKey {key:?}"),
+        Frame::Auth(key) => format!("Auth {key:?}"),
+        Frame::AuthOk => "AuthOk".to_string(),
+        Frame::AuthFailed => "AuthFailed".to_string(),
+        Frame::Exec(command) => format!("Exec {command:?}"),
+        Frame::Stdin(data) => format!("Stdin {:?}", BStr::new(data)),
+        Frame::StdinEof => "StdinEof".to_string(),
+        Frame::Stdout(data) => format!("Stdout {:?}", BStr::new(data)),
+        Frame::Stderr(data) => format!("Stderr {:?}", BStr::new(data)),
+        Frame::Exit(code) => format!("Exit {code}"),
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Formats a trace file for reading, with the bytes of each direction of each connection
+/// split into the stand-in's messages, each shown at the time its last byte arrived.
+///
+/// Bytes that are not the stand-in's messages, such as a real ssh server's, are shown as they
+/// were read, as are any left over when a connection ended part way through a message.
+pub fn pretty(trace: &str) -> String {
+    let mut out = String::new();
+    let mut streams: HashMap<(String, String, String), Stream> = HashMap::new();
+    for line in trace.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            if header.starts_with("run ") {
+                streams.clear();
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+            }
+            writeln!(out, "{header}").unwrap();
+            continue;
+        }
+
+        let mut fields = line.splitn(4, ' ');
+        let (Some(time), Some(remote), Some(conn), Some(event)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            writeln!(out, "  malformed line {line:?}").unwrap();
+            continue;
+        };
+        let prefix = format!("  {time} {remote} {conn}");
+        let Some((direction @ (">" | "<"), data)) = event.split_once(' ') else {
+            // an event of the connection as a whole
+            writeln!(out, "{prefix}    {event}").unwrap();
+            continue;
+        };
+
+        let key = (remote.to_string(), conn.to_string(), direction.to_string());
+        let stream = streams.entry(key).or_default();
+        if data == "eof" {
+            if !stream.buf.is_empty() {
+                let partial = BStr::new(&stream.buf);
+                writeln!(out, "{prefix} {direction}  incomplete {partial:?}").unwrap();
+                stream.buf.clear();
+            }
+            writeln!(out, "{prefix} {direction}  eof").unwrap();
+            continue;
+        }
+        let Some(bytes) = decode_hex(data) else {
+            writeln!(out, "  malformed line {line:?}").unwrap();
+            continue;
+        };
+        stream.buf.extend(bytes);
+        for message in stream.messages() {
+            writeln!(out, "{prefix} {direction}  {message}").unwrap();
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TempDir;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("test_2_1::test_remote/r1+2"),
+            "test_2_1.test_remote_r1_2.trace"
+        );
+    }
+
+    #[test]
+    fn test_proxy_records_messages() {
+        let dir = TempDir::new("pars-trace").unwrap();
+        let path = dir.join("run.trace");
+        let server = TcpListener::bind((HOST, 0)).unwrap();
+        let port = server.local_addr().unwrap().port();
+        let mut remotes = [RemoteHost::new(HOST, port, 1)];
+        let tracer = Tracer::start(&path, &mut remotes).unwrap();
+        assert_ne!(remotes[0].remote.port, port);
+
+        let mut client = TcpStream::connect(("127.0.0.1", remotes[0].remote.port)).unwrap();
+        let (mut accepted, _) = server.accept().unwrap();
+        // a message split across two reads, then one that is cut off
+        let exec = Frame::Exec("echo hi".to_string()).to_bytes();
+        client.write_all(&exec[..3]).unwrap();
+        client.flush().unwrap();
+        let mut received = vec![0; exec.len()];
+        accepted.read_exact(&mut received[..3]).unwrap();
+        client.write_all(&exec[3..]).unwrap();
+        client
+            .write_all(&Frame::Stdout(b"hi\n".to_vec()).to_bytes()[..4])
+            .unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        accepted.read_exact(&mut received[3..]).unwrap();
+        assert_eq!(received, exec);
+        // the end is recorded before it is passed on
+        let mut cut_off = Vec::new();
+        accepted.read_to_end(&mut cut_off).unwrap();
+        Frame::Exit(0).write_to(&mut accepted).unwrap();
+        drop(accepted);
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, Frame::Exit(0).to_bytes());
+        drop(tracer);
+
+        let pretty = pretty(&fs::read_to_string(&path).unwrap());
+        let messages: Vec<_> = pretty
+            .lines()
+            .filter_map(|line| line.split_once(" r0 c0 ").map(|(_, message)| message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "   open",
+                ">  Exec \"echo hi\"",
+                ">  incomplete \"1\\0\\0\\0\"",
+                ">  eof",
+                "<  Exit 0",
+                "<  eof",
+            ]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Benchmarks of how quickly pars runs fixed workloads.
+//!
+//! Each workload is run once per distribution: locally with 1 up to `--max-jobs` threads,
+//! and on any remotes given with `--remote`. The results are printed as a table, written to
+//! `bench_output.txt`, and optionally written as JSON so builds can be compared.
+//!
+//! # Workloads
+//! - `dispatch`: lines of `true`, so the time taken is almost all pars' own overhead.
+//! - `cpu`: lines that each spin a CPU for a while, to show how pars scales with threads.
+//! - `latency`: lines written one at a time with a gap between them. Each prints the time it
+//!   started, which gives the latency from writing a line to it starting to run. The time
+//!   spent waiting between lines is left out of its seconds, per line time and speedup.
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use pars_testkit::{Distribution, ParsProgram, RemoteHost, RunResult};
+
+use crate::find_pars;
+
+/// The command run by each line of the `cpu` workload
+const CPU_LINE: &str = "sh -c 'i=0; while [ $i -lt 50000 ]; do i=$((i+1)); done'";
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Path of the pars executable to measure
+    pars: PathBuf,
+
+    /// Highest number of local threads to measure with, starting from 1
+    /// (defaults to the number of CPUs)
+    #[arg(short = 'J', long)]
+    max_jobs: Option<u32>,
+
+    /// Also measure with these remotes together, each given as `host:port/threads`
+    #[arg(long, value_name = "HOST:PORT/THREADS", value_parser = parse_remote)]
+    remote: Vec<RemoteHost>,
+
+    /// Number of lines in the `dispatch` workload
+    #[arg(long, default_value_t = 10_000)]
+    lines: usize,
+
+    /// Number of lines in the `cpu` workload
+    #[arg(long, default_value_t = 32)]
+    cpu_lines: usize,
+
+    /// Number of lines in the `latency` workload
+    #[arg(long, default_value_t = 500)]
+    latency_lines: usize,
+
+    /// Milliseconds between lines in the `latency` workload
+    #[arg(long, default_value_t = 2)]
+    interval_ms: u64,
+
+    /// Name for this build of pars, saved with the results
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Where to write the table of results
+    #[arg(long, value_name = "PATH", default_value = "bench_output.txt")]
+    output: PathBuf,
+
+    /// Also write the results as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    json: Option<PathBuf>,
+}
+
+fn parse_remote(s: &str) -> Result<RemoteHost, String> {
+    if s.split([':', '/']).count() != 3 {
+        return Err("expected `host:port/threads`".to_string());
+    }
+    s.parse().map_err(|e| format!("{e}"))
+}
+
+/// Latency percentiles of the lines in a run, in milliseconds
+struct Latency {
+    p50: f64,
+    p99: f64,
+    max: f64,
+}
+
+/// The result of running one workload with one distribution.
+struct Measurement {
+    workload: &'static str,
+    distribution: String,
+    lines: usize,
+    seconds: f64,
+    /// Time taken from the first line of the same workload divided by this one's
+    speedup: f64,
+    latency: Option<Latency>,
+}
+impl Measurement {
+    fn per_line_us(&self) -> f64 {
+        self.seconds * 1e6 / self.lines as f64
+    }
+}
+
+fn now_ns() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos()
+}
+
+fn spawn(pars: &Path, distribution: &Distribution) -> ParsProgram {
+    ParsProgram::builder(pars)
+        .distribution(distribution.clone())
+        .spawn()
+        .expect("Failed to start pars")
+}
+
+/// Warns when pars did not run a workload cleanly, as its timings are then meaningless.
+fn check_run(workload: &str, distribution: &Distribution, result: &RunResult) {
+    if !result.success() {
+        eprintln!(
+            "warning: pars finished with {} running `{workload}` on {}",
+            result.exit(),
+            distribution.label()
+        );
+    }
+}
+
+/// Runs all lines at once and returns the seconds pars took.
+fn run_batch(pars: &Path, distribution: &Distribution, line: &str, lines: usize) -> f64 {
+    let mut program = spawn(pars, distribution);
+    program.run_cmds(std::iter::repeat_n(line, lines));
+    let result = program.finish();
+    check_run(line, distribution, &result);
+
+    result.duration.as_secs_f64()
+}
+
+/// Writes lines one at a time, `interval` apart, and measures how long each took to start.
+///
+/// Returns the seconds pars took, less the time spent waiting between lines, along with the
+/// latencies.
+fn run_paced(
+    pars: &Path,
+    distribution: &Distribution,
+    lines: usize,
+    interval: Duration,
+) -> (f64, Latency) {
+    let mut program = spawn(pars, distribution);
+    let mut written = Vec::with_capacity(lines);
+    let mut waited = Duration::ZERO;
+    for i in 0..lines {
+        written.push(now_ns());
+        program.run_cmds([format!("echo {i}; date +%s%N")]);
+        let start = Instant::now();
+        thread::sleep(interval);
+        waited += start.elapsed();
+    }
+    let result = program.finish();
+    check_run("latency", distribution, &result);
+
+    // each line prints its index, then the time it started
+    let mut latencies: Vec<f64> = result
+        .stdout
+        .chunks_exact(2)
+        .filter_map(|pair| {
+            let i: usize = pair[0].parse().ok()?;
+            let started: u128 = pair[1].parse().ok()?;
+            Some(started.saturating_sub(*written.get(i)?) as f64 / 1e6)
+        })
+        .collect();
+    if latencies.len() != lines {
+        eprintln!(
+            "warning: only {} of {lines} lines of `latency` ran on {}",
+            latencies.len(),
+            distribution.label()
+        );
+    }
+    latencies.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+        let i = ((latencies.len() as f64 * p).ceil() as usize).saturating_sub(1);
+        latencies.get(i).copied().unwrap_or(f64::NAN)
+    };
+
+    let latency = Latency {
+        p50: percentile(0.5),
+        p99: percentile(0.99),
+        max: percentile(1.0),
+    };
+    let seconds = result.duration.saturating_sub(waited).as_secs_f64();
+    (seconds, latency)
+}
+
+fn table(pars: &Path, label: Option<&str>, measurements: &[Measurement]) -> String {
+    let mut out = String::new();
+    writeln!(out, "pars benchmark: {}", pars.display()).unwrap();
+    if let Some(label) = label {
+        writeln!(out, "label: {label}").unwrap();
+    }
+    writeln!(
+        out,
+        "\n{:<10} {:<14} {:>7} {:>9} {:>13} {:>8} {:>9} {:>9} {:>9}",
+        "workload",
+        "distribution",
+        "lines",
+        "seconds",
+        "per line (us)",
+        "speedup",
+        "p50 (ms)",
+        "p99 (ms)",
+        "max (ms)"
+    )
+    .unwrap();
+    for m in measurements {
+        write!(
+            out,
+            "{:<10} {:<14} {:>7} {:>9.3} {:>13.1} {:>8.2}",
+            m.workload,
+            m.distribution,
+            m.lines,
+            m.seconds,
+            m.per_line_us(),
+            m.speedup
+        )
+        .unwrap();
+        if let Some(l) = &m.latency {
+            write!(out, " {:>9.2} {:>9.2} {:>9.2}", l.p50, l.p99, l.max).unwrap();
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Escapes a string for use in JSON
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats a number for JSON, which has no representation of NaN
+fn json_num(n: f64) -> String {
+    if n.is_finite() {
+        format!("{n:.6}")
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json(pars: &Path, label: Option<&str>, measurements: &[Measurement]) -> String {
+    let rows: Vec<String> = measurements
+        .iter()
+        .map(|m| {
+            let mut row = format!(
+                "    {{\"workload\": {}, \"distribution\": {}, \"lines\": {}, \"seconds\": {}, \"per_line_us\": {}, \"speedup\": {}",
+                json_str(m.workload),
+                json_str(&m.distribution),
+                m.lines,
+                json_num(m.seconds),
+                json_num(m.per_line_us()),
+                json_num(m.speedup),
+            );
+            if let Some(l) = &m.latency {
+                write!(
+                    row,
+                    ", \"latency_ms\": {{\"p50\": {}, \"p99\": {}, \"max\": {}}}",
+                    json_num(l.p50),
+                    json_num(l.p99),
+                    json_num(l.max)
+                )
+                .unwrap();
+            }
+            row.push('}');
+            row
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"pars\": {},\n  \"label\": {},\n  \"timestamp\": {},\n  \"results\": [\n{}\n  ]\n}}\n",
+        json_str(&pars.display().to_string()),
+        label.map_or("null".to_string(), json_str),
+        now_ns() / 1_000_000_000,
+        rows.join(",\n")
+    )
+}
+
+pub fn main(args: BenchArgs) -> ExitCode {
+    let Some(pars) = find_pars(&args.pars) else {
+        return ExitCode::FAILURE;
+    };
+
+    let max_jobs = args
+        .max_jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get() as u32));
+    let mut distributions: Vec<_> = (1..=max_jobs.max(1)).map(Distribution::Local).collect();
+    if !args.remote.is_empty() {
+        distributions.push(Distribution::Remote(args.remote.clone()));
+    }
+    let interval = Duration::from_millis(args.interval_ms);
+
+    let mut measurements = Vec::new();
+    for (workload, line, lines) in [
+        ("dispatch", "true", args.lines),
+        ("cpu", CPU_LINE, args.cpu_lines),
+        ("latency", "", args.latency_lines),
+    ] {
+        let mut baseline = None;
+        for distribution in &distributions {
+            eprintln!("running {workload} on {}", distribution.label());
+            let (seconds, latency) = match workload {
+                "latency" => {
+                    let (seconds, latency) = run_paced(&pars, distribution, lines, interval);
+                    (seconds, Some(latency))
+                }
+                _ => (run_batch(&pars, distribution, line, lines), None),
+            };
+            let baseline = *baseline.get_or_insert(seconds);
+
+            measurements.push(Measurement {
+                workload,
+                distribution: distribution.label(),
+                lines,
+                seconds,
+                speedup: baseline / seconds,
+                latency,
+            });
+        }
+    }
+
+    let label = args.label.as_deref();
+    let table = table(&pars, label, &measurements);
+    print!("\n{table}");
+    if let Err(e) = fs::write(&args.output, &table) {
+        eprintln!("error: failed to write {}: {e}", args.output.display());
+        return ExitCode::FAILURE;
+    }
+    if let Some(path) = &args.json {
+        if let Err(e) = fs::write(path, json(&pars, label, &measurements)) {
+            eprintln!("error: failed to write {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
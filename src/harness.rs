@@ -0,0 +1,76 @@
+//! Glue between the suites and `pars_testkit`: the settings shared by every test in this run,
+//! and shorthands for launching the pars binary under test and remote stand-ins for it.
+use std::sync::OnceLock;
+
+use crate::suites::test_reconnect::Outcome;
+
+pub use pars_testkit::{
+    Distribution, ParsProgram, RemoteHost, StandIn, StandInBuilder, TerminationType,
+};
+
+/// Settings shared by every test in a run, filled in from the command line.
+#[derive(Debug)]
+pub struct Config {
+    /// Hostname of a real remote for the remote suites, if one was given
+    pub host: Option<String>,
+    /// Port of a real remote for the remote suites, if one was given
+    pub remote_port: Option<u16>,
+    /// What pars should do when a connection drops, if the reconnect suite should check it
+    pub reconnect_policy: Option<Outcome>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Sets the config for this run. Must be called once, before any test starts.
+pub fn configure(config: Config) {
+    CONFIG.set(config).expect("config already set");
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get().expect("config not set")
+}
+
+/// A remote for the remote suites, running `threads` lines at once.
+///
+/// This is the real remote given with `--host`/`--remote-port`, if either was given, and
+/// otherwise a stand-in of the test's own (see `RemoteHost::stand_in`).
+pub fn remote(threads: u32) -> RemoteHost {
+    let config = config();
+    if config.host.is_none() && config.remote_port.is_none() {
+        return RemoteHost::stand_in(threads);
+    }
+
+    let host = config.host.as_deref().unwrap_or("localhost");
+    RemoteHost::new(host, config.remote_port.unwrap_or(1234), threads)
+}
+
+/// Starts the pars binary under test with the given distribution and halt mode.
+pub fn launch(distribution: Distribution, term_type: Option<TerminationType>) -> ParsProgram {
+    ParsProgram::from_env()
+        .distribution(distribution)
+        .halt_opt(term_type)
+        .spawn()
+        .expect("Failed to start pars")
+}
+
+/// Starts configuring a stand-in ssh server, whose `ssh` client is this executable and whose
+/// remote commands can run the pars binary under test as `pars`.
+pub fn stand_in() -> StandInBuilder {
+    StandIn::from_env()
+}
+
+/// Starts the pars binary under test, pointed at `stand_in`, with the given distribution
+/// and halt mode.
+pub fn launch_with(
+    stand_in: &StandIn,
+    distribution: Distribution,
+    term_type: Option<TerminationType>,
+) -> ParsProgram {
+    let builder = ParsProgram::from_env()
+        .distribution(distribution)
+        .halt_opt(term_type);
+    stand_in
+        .setup(builder)
+        .spawn()
+        .expect("Failed to start pars")
+}
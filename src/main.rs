@@ -0,0 +1,214 @@
+//! # pars-autotest
+//! Self written autotests for pars, run as a black box against a compiled pars binary.
+//!
+//! # Usage
+//! ```text
+//! pars-autotest ~/pars/target/debug/pars            # run every suite
+//! pars-autotest ~/pars/target/debug/pars 1.1 2.3    # run only tasks 1.1 and 2.3
+//! pars-autotest -j 4 --report test_output.txt ~/pars/target/debug/pars 1
+//! pars-autotest bench ~/pars/target/debug/pars     # measure how fast pars is
+//! pars-autotest --trace traces ~/pars/target/debug/pars 2.1
+//! pars-autotest trace traces/test_2_1.test_remote_r1.trace   # read a recorded trace
+//! ```
+//!
+//! By default, each test in the remote suites (`2.x`) runs against a stand-in ssh server of its
+//! own, so no remote is needed. To test against a real remote instead (e.g. birdie on CSE), give
+//! its `--host`/`--remote-port`; pars itself looks for your ssh key at `~/.ssh/cs6991/cs6991-id`.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+    time::Duration,
+};
+
+use clap::{Args, Parser, Subcommand};
+use pars_testkit::{AUDIT_VAR, BINARY_VAR, TRACE_VAR};
+
+mod bench;
+mod harness;
+mod runner;
+mod suites;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    test: TestArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Measure the scheduling overhead and scaling of pars
+    Bench(bench::BenchArgs),
+    /// Print a trace recorded with --trace, split into messages
+    Trace {
+        /// The trace file to print
+        path: PathBuf,
+    },
+}
+
+/// Runs the test suites (the default when no subcommand is given)
+#[derive(Args)]
+struct TestArgs {
+    /// Path of the pars executable to test
+    #[arg(required = true)]
+    pars: Option<PathBuf>,
+
+    /// Only run suites matching these filters, e.g. `1.1`, `2` or a test name
+    filters: Vec<String>,
+
+    /// Number of tests to run in parallel (defaults to the number of CPUs)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Also write the results to this file
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// Hostname of a real remote for the remote suites to use, rather than a stand-in for each
+    /// test (defaults to localhost if only --remote-port is given)
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Port of a real remote for the remote suites to use, rather than a stand-in for each test
+    /// (defaults to 1234 if only --host is given)
+    #[arg(long)]
+    remote_port: Option<u16>,
+
+    /// Most stand-in remotes running at once, across every test
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    max_stand_ins: usize,
+
+    /// Seconds a test may run before it is failed, unless the test sets its own timeout
+    #[arg(long, default_value_t = 60)]
+    timeout: u64,
+
+    /// Fail the reconnect suite unless pars deals with each dropped connection this way, e.g.
+    /// rerun-elsewhere; otherwise what pars does is only recorded
+    #[arg(long, value_name = "OUTCOME")]
+    reconnect_policy: Option<suites::test_reconnect::Outcome>,
+
+    /// Record everything sent to and from each test's remotes, with timestamps, in a trace
+    /// file per test in this directory, to read with `pars-autotest trace`. Only for stand-in
+    /// remotes, as ssh would not trust the proxy in front of a real one
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["host", "remote_port"])]
+    trace: Option<PathBuf>,
+
+    /// Fail any test after which pars leaves processes behind, such as zombies, orphaned
+    /// commands or ssh sessions
+    #[arg(long)]
+    audit: bool,
+}
+
+/// Resolves the path of the pars binary, printing an error if it does not exist.
+fn find_pars(path: &Path) -> Option<PathBuf> {
+    match path.canonicalize() {
+        Ok(pars) if pars.is_file() => Some(pars),
+        _ => {
+            eprintln!("error: pars binary not found at {}", path.display());
+            None
+        }
+    }
+}
+
+/// Prints a trace file split into messages
+fn print_trace(path: &Path) -> ExitCode {
+    match fs::read_to_string(path) {
+        Ok(trace) => {
+            print!("{}", pars_testkit::trace::pretty(&trace));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: failed to read {}: {e}", path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    // the remote stand-in runs this executable as its `ssh` client
+    let arg0 = env::args_os().next().unwrap_or_default();
+    if Path::new(&arg0).file_name() == Some("ssh".as_ref()) {
+        return pars_testkit::ssh_main();
+    }
+
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Bench(args)) => bench::main(args),
+        Some(Command::Trace { path }) => print_trace(&path),
+        None => test_main(cli.test),
+    }
+}
+
+fn test_main(cli: TestArgs) -> ExitCode {
+    let Some(pars) = cli.pars.as_deref().and_then(find_pars) else {
+        return ExitCode::FAILURE;
+    };
+
+    // the suites launch pars through `ParsProgram::from_env`
+    env::set_var(BINARY_VAR, pars);
+    if cli.audit {
+        env::set_var(AUDIT_VAR, "1");
+    }
+    if let Some(dir) = &cli.trace {
+        if let Err(e) = fs::create_dir_all(dir) {
+            eprintln!("error: failed to create {}: {e}", dir.display());
+            return ExitCode::FAILURE;
+        }
+        env::set_var(TRACE_VAR, dir);
+    }
+    harness::configure(harness::Config {
+        host: cli.host,
+        remote_port: cli.remote_port,
+        reconnect_policy: cli.reconnect_policy,
+    });
+    pars_testkit::set_stand_in_limit(cli.max_stand_ins);
+
+    let jobs = cli
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    let timeout = Duration::from_secs(cli.timeout);
+    let results = runner::run(suites::SUITES, &cli.filters, jobs, timeout);
+    print!("{}", runner::summary(&results));
+
+    if let Some(path) = cli.report {
+        if let Err(e) = runner::write_report(&path, &results) {
+            eprintln!("error: failed to write report to {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if results.iter().all(|r| r.failure.is_none()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+
+    use super::*;
+
+    #[test]
+    fn test_trace_needs_stand_ins() {
+        for remote in [["--host", "birdie"], ["--remote-port", "22"]] {
+            let args = ["pars-autotest", "--trace", "traces", "pars"];
+            let err = Cli::try_parse_from(args.into_iter().chain(remote))
+                .err()
+                .expect("--trace was accepted with a real remote");
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        }
+        assert!(Cli::try_parse_from(["pars-autotest", "--trace", "traces", "pars"]).is_ok());
+    }
+}
@@ -0,0 +1,272 @@
+//! Runs the selected suites against the pars binary and reports the results.
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt::Write as _,
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{mpsc::channel, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use pars_testkit::{cancel, report, TestCase};
+
+use crate::suites::Suite;
+
+/// A test picked out of a suite, ready to be run.
+struct Job {
+    /// Full name of the test, e.g. `test_1_1::test_simple_output`
+    name: String,
+    timeout: Duration,
+    case: TestCase,
+}
+
+pub struct TestResult {
+    pub name: String,
+    pub duration: Duration,
+    /// `None` on success, otherwise the panic message of the failed test
+    pub failure: Option<String>,
+    /// Values the test recorded with `pars_testkit::report::record`
+    pub notes: Vec<(String, String)>,
+}
+
+/// Returns true if the given suite or test is selected by the filters.
+///
+/// A filter made of digits and dots matches a suite by its id (`1.1`) or a group of
+/// suites by prefix (`2`). Any other filter matches a suite with that id (`exit`), or tests
+/// whose full name contains it.
+fn selected(filters: &[String], suite: &Suite, test_name: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|f| {
+            if f.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                suite.id == f || suite.id.starts_with(&format!("{f}."))
+            } else {
+                suite.id == f || test_name.contains(f.as_str())
+            }
+        })
+}
+
+/// Extracts the message from a panic payload (as produced by `assert!`, `unwrap`, etc.)
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "test panicked".to_string()
+    }
+}
+
+/// Runs a test on its own thread, failing it if it does not finish within its timeout.
+///
+/// A test that times out is left running in the background, as there is no way to stop it,
+/// but it is cancelled: every pars it started is killed, along with the rest of its process
+/// group, so it does not hold up the tests after it.
+fn run_job(job: &Job) -> TestResult {
+    let start = Instant::now();
+    let run = Arc::clone(&job.case.run);
+    let token = cancel::Token::new();
+    let entered = token.clone();
+    let (tx, rx) = channel();
+    // named after the test, which names its trace file, if tracing
+    thread::Builder::new()
+        .name(job.name.clone())
+        .spawn(move || {
+            entered.enter();
+            let failure = panic::catch_unwind(AssertUnwindSafe(|| run()))
+                .err()
+                .map(panic_message);
+            let _ = tx.send((failure, report::take()));
+        })
+        .expect("Failed to start test thread");
+
+    let (failure, notes) = match rx.recv_timeout(job.timeout) {
+        Ok(result) => result,
+        Err(_) => {
+            token.cancel();
+            (
+                Some(format!(
+                    "test timed out after {}s",
+                    job.timeout.as_secs_f64()
+                )),
+                Vec::new(),
+            )
+        }
+    };
+
+    TestResult {
+        name: job.name.clone(),
+        duration: start.elapsed(),
+        failure,
+        notes,
+    }
+}
+
+fn print_result(result: &TestResult) {
+    let status = match result.failure {
+        None => "ok",
+        Some(_) => "FAILED",
+    };
+    println!("test {} ... {status}", result.name);
+    for (key, value) in &result.notes {
+        println!("    {key}: {value}");
+    }
+}
+
+/// Runs every selected test, using up to `jobs` threads for the parallel suites.
+/// Serial tests, and tests in serial suites, are run one at a time after the parallel ones finish.
+///
+/// Tests without their own timeout are given `timeout`.
+pub fn run(
+    suites: &[Suite],
+    filters: &[String],
+    jobs: usize,
+    timeout: Duration,
+) -> Vec<TestResult> {
+    let mut parallel = VecDeque::new();
+    let mut serial = Vec::new();
+    for suite in suites {
+        for test in (suite.tests)() {
+            let name = format!("{}::{}", suite.name, test.name);
+            if !selected(filters, suite, &name) {
+                continue;
+            }
+
+            let serial_test = suite.serial || test.serial;
+            let job = Job {
+                name,
+                timeout: test.timeout.unwrap_or(timeout),
+                case: test,
+            };
+            if serial_test {
+                serial.push(job);
+            } else {
+                parallel.push_back(job);
+            }
+        }
+    }
+    println!("running {} tests", parallel.len() + serial.len());
+
+    // failures are reported by the runner, not the default panic hook
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut results = Vec::new();
+    let queue = Arc::new(Mutex::new(parallel));
+    let (tx, rx) = channel();
+    let workers: Vec<_> = (0..jobs.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                let Some(job) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+                tx.send(run_job(&job)).unwrap();
+            })
+        })
+        .collect();
+    drop(tx);
+
+    for result in rx {
+        print_result(&result);
+        results.push(result);
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    for job in serial {
+        let result = run_job(&job);
+        print_result(&result);
+        results.push(result);
+    }
+
+    let _ = panic::take_hook();
+    results
+}
+
+/// Formats the failures and totals of a run, in the style of `cargo test`.
+pub fn summary(results: &[TestResult]) -> String {
+    let mut out = String::new();
+    let failed: Vec<_> = results.iter().filter(|r| r.failure.is_some()).collect();
+
+    if !failed.is_empty() {
+        writeln!(out, "\nfailures:").unwrap();
+        for result in &failed {
+            let msg = result.failure.as_deref().unwrap_or_default();
+            writeln!(out, "\n---- {} ----\n{msg}", result.name).unwrap();
+        }
+    }
+
+    let status = if failed.is_empty() { "ok" } else { "FAILED" };
+    writeln!(
+        out,
+        "\ntest result: {status}. {} passed; {} failed",
+        results.len() - failed.len(),
+        failed.len()
+    )
+    .unwrap();
+
+    out
+}
+
+/// Writes a report with one line per test, followed by the summary.
+pub fn write_report(path: &Path, results: &[TestResult]) -> io::Result<()> {
+    let mut out = String::new();
+    for result in results {
+        let status = match result.failure {
+            None => "ok",
+            Some(_) => "FAILED",
+        };
+        writeln!(
+            out,
+            "{:<60} {status:<6} {:>8.2}s",
+            result.name,
+            result.duration.as_secs_f64()
+        )
+        .unwrap();
+        for (key, value) in &result.notes {
+            writeln!(out, "    {key}: {value}").unwrap();
+        }
+    }
+    out.push_str(&summary(results));
+
+    fs::write(path, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suite(id: &'static str) -> Suite {
+        Suite {
+            id,
+            name: "test_x",
+            serial: false,
+            tests: Vec::new,
+        }
+    }
+
+    #[test]
+    fn test_filter_by_suite_id() {
+        let filters = vec!["1.1".to_string()];
+        assert!(selected(&filters, &suite("1.1"), "test_1_1::foo"));
+        assert!(!selected(&filters, &suite("1.2"), "test_1_2::foo"));
+        assert!(!selected(&filters, &suite("2.1"), "test_2_1::foo"));
+    }
+
+    #[test]
+    fn test_filter_by_prefix_and_name() {
+        let filters = vec!["2".to_string(), "lazy".to_string()];
+        assert!(selected(&filters, &suite("2.3"), "test_2_3::foo"));
+        assert!(selected(
+            &filters,
+            &suite("1.3"),
+            "test_1_3::test_lazy_mode_1"
+        ));
+        assert!(!selected(&filters, &suite("1.2"), "test_1_2::foo"));
+        assert!(selected(&[], &suite("1.2"), "test_1_2::foo"));
+    }
+}
@@ -0,0 +1,185 @@
+//! The test suites, one module per section of the assignment.
+use pars_testkit::TestCase;
+
+mod test_1_1;
+mod test_1_2;
+mod test_1_3;
+mod test_2_1;
+mod test_2_2;
+mod test_2_3;
+mod test_binary_output;
+mod test_cli;
+mod test_concurrency;
+mod test_environment;
+mod test_exit_status;
+mod test_idle_cpu;
+mod test_matrix;
+mod test_mixed_distribution;
+mod test_multiline_input;
+mod test_output_framing;
+pub(crate) mod test_reconnect;
+mod test_remote_args;
+mod test_remote_eager;
+mod test_shell_semantics;
+mod test_signals;
+mod test_ssh_auth;
+mod test_stdin_isolation;
+mod test_stress;
+
+pub struct Suite {
+    /// Short id used to select the suite from the command line, e.g. `1.1`.
+    /// Suites that are not part of an assignment task use a word instead.
+    pub id: &'static str,
+    /// Module name, used as the prefix of each test's full name
+    pub name: &'static str,
+    /// Whether the suite's tests must be run one at a time
+    pub serial: bool,
+    pub tests: fn() -> Vec<TestCase>,
+}
+
+pub const SUITES: &[Suite] = &[
+    Suite {
+        id: "1.1",
+        name: "test_1_1",
+        serial: false,
+        tests: test_1_1::tests,
+    },
+    Suite {
+        id: "1.2",
+        name: "test_1_2",
+        serial: false,
+        tests: test_1_2::tests,
+    },
+    Suite {
+        id: "1.3",
+        name: "test_1_3",
+        serial: false,
+        tests: test_1_3::tests,
+    },
+    Suite {
+        id: "2.1",
+        name: "test_2_1",
+        serial: false,
+        tests: test_2_1::tests,
+    },
+    Suite {
+        id: "2.2",
+        name: "test_2_2",
+        serial: false,
+        tests: test_2_2::tests,
+    },
+    Suite {
+        id: "2.3",
+        name: "test_2_3",
+        serial: false,
+        tests: test_2_3::tests,
+    },
+    Suite {
+        id: "matrix",
+        name: "test_matrix",
+        serial: false,
+        tests: test_matrix::tests,
+    },
+    Suite {
+        id: "exit",
+        name: "test_exit_status",
+        serial: false,
+        tests: test_exit_status::tests,
+    },
+    Suite {
+        id: "shell",
+        name: "test_shell_semantics",
+        serial: false,
+        tests: test_shell_semantics::tests,
+    },
+    Suite {
+        id: "input",
+        name: "test_multiline_input",
+        serial: false,
+        tests: test_multiline_input::tests,
+    },
+    Suite {
+        id: "binary",
+        name: "test_binary_output",
+        serial: false,
+        tests: test_binary_output::tests,
+    },
+    Suite {
+        id: "stress",
+        name: "test_stress",
+        serial: false,
+        tests: test_stress::tests,
+    },
+    Suite {
+        id: "signal",
+        name: "test_signals",
+        serial: false,
+        tests: test_signals::tests,
+    },
+    Suite {
+        id: "idle",
+        name: "test_idle_cpu",
+        serial: false,
+        tests: test_idle_cpu::tests,
+    },
+    Suite {
+        id: "concurrency",
+        name: "test_concurrency",
+        serial: false,
+        tests: test_concurrency::tests,
+    },
+    Suite {
+        id: "env",
+        name: "test_environment",
+        serial: false,
+        tests: test_environment::tests,
+    },
+    Suite {
+        id: "stdin",
+        name: "test_stdin_isolation",
+        serial: false,
+        tests: test_stdin_isolation::tests,
+    },
+    Suite {
+        id: "ssh",
+        name: "test_ssh_auth",
+        serial: false,
+        tests: test_ssh_auth::tests,
+    },
+    Suite {
+        id: "remote_args",
+        name: "test_remote_args",
+        serial: false,
+        tests: test_remote_args::tests,
+    },
+    Suite {
+        id: "cli",
+        name: "test_cli",
+        serial: false,
+        tests: test_cli::tests,
+    },
+    Suite {
+        id: "framing",
+        name: "test_output_framing",
+        serial: false,
+        tests: test_output_framing::tests,
+    },
+    Suite {
+        id: "eager_remote",
+        name: "test_remote_eager",
+        serial: false,
+        tests: test_remote_eager::tests,
+    },
+    Suite {
+        id: "mixed",
+        name: "test_mixed_distribution",
+        serial: false,
+        tests: test_mixed_distribution::tests,
+    },
+    Suite {
+        id: "reconnect",
+        name: "test_reconnect",
+        serial: false,
+        tests: test_reconnect::tests,
+    },
+];
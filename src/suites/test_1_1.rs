@@ -0,0 +1,105 @@
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_simple_output(),
+        test_simple_no_output(),
+        test_some_output(),
+        test_with_error_line(),
+        test_multiple_commands(),
+        test_empty_file(),
+        test_commands_ordering(),
+        test_never_termination(),
+    ]
+}
+
+pars_test! {
+    test_simple_output {
+        distribution: Distribution::Local(1),
+        input: ["echo \"hello world\"", "echo foo", "echo bar", "\r"],
+        expect: ["hello world", "foo", "bar"],
+    }
+}
+
+pars_test! {
+    test_simple_no_output {
+        distribution: Distribution::Local(1),
+        input: ["true"],
+        expect: [],
+    }
+}
+
+pars_test! {
+    test_some_output {
+        distribution: Distribution::Local(1),
+        input: [
+            "true",
+            "echo 1",
+            "true",
+            "echo 2",
+            "true",
+            "echo 3",
+            "true",
+            "echo 4",
+            "true",
+            "echo 5",
+            "true",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_with_error_line {
+        distribution: Distribution::Local(1),
+        input: [
+            "echo \"hello\"; echo \"world\"",
+            "echo \"you can see this\"; /bin/false; echo \"can't see this\"",
+            "echo \"cheeky; echo semicolon\"",
+            "\r",
+        ],
+        expect: [
+            "hello",
+            "world",
+            "you can see this",
+            "cheeky; echo semicolon", // stringify producing incorrect output
+        ],
+    }
+}
+
+pars_test! {
+    test_multiple_commands {
+        distribution: Distribution::Local(1),
+        input: ["echo 1; echo 2", "echo 3; true; echo 4;", "echo 5; true", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_empty_file {
+        distribution: Distribution::Local(1),
+        input: [stringify!(cat << EOF)],
+        expect: [],
+    }
+}
+
+pars_test! {
+    /// commands should only run one a time and in serial
+    test_commands_ordering {
+        distribution: Distribution::Local(1),
+        input: ["sleep 1; echo 1", "echo 2", "echo 3", "echo 4", "echo 5", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// test never termination control (default)
+    test_never_termination {
+        distribution: Distribution::Local(1),
+        input: ["echo 1; false; echo 1", "echo 2; echo 3; echo 4; echo 5", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
@@ -0,0 +1,72 @@
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_simple_2_threads(),
+        test_completion_order_2_threads(),
+        test_multiple_commands_at_once(),
+        test_output_line_buffering(),
+        test_never_termination(),
+        test_number_of_threads(),
+    ]
+}
+
+pars_test! {
+    test_simple_2_threads {
+        distribution: Distribution::Local(2),
+        input: ["echo hello", "sleep 1", "echo world", "\r"],
+        expect: ["hello", "world"],
+    }
+}
+
+pars_test! {
+    test_completion_order_2_threads {
+        distribution: Distribution::Local(2),
+        input: ["sleep 1; echo hello", "echo world", "\r"],
+        expect: ["world", "hello"],
+    }
+}
+
+pars_test! {
+    test_multiple_commands_at_once {
+        distribution: Distribution::Local(2),
+        input: ["echo 1;", "sleep 1; echo 5", "echo 2;", "echo 3;", "echo 4;", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// output of a line should be buffered
+    test_output_line_buffering {
+        distribution: Distribution::Local(2),
+        input: ["echo 3; sleep 2; echo 4; echo 5", "echo 1; echo 2", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// never mode: existing lines should finish, new lines should run
+    test_never_termination {
+        distribution: Distribution::Local(2),
+        input: ["sleep 1; echo 4; echo 5", "echo 1; false; echo 1", "echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// job limit / thread number should be respected
+    test_number_of_threads {
+        distribution: Distribution::Local(4),
+        input: [
+            "sleep 1; echo 1",
+            "sleep 1.5; echo 3",
+            "sleep 2; echo 4",
+            "sleep 2.5; echo 5",
+            "echo 2;",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
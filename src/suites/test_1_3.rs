@@ -0,0 +1,64 @@
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_lazy_mode_1(),
+        test_lazy_mode_2(),
+        test_eager_mode(),
+        never_mode(),
+    ]
+}
+
+pars_test! {
+    /// lazy mode: existing lines should finish, new lines should not start
+    test_lazy_mode_1 {
+        distribution: Distribution::Local(2),
+        halt: TerminationType::Lazy,
+        input: ["sleep 1; echo 4; echo 5", "echo 1; echo 2; echo 3", "false", "echo 1", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// lazy mode: the current line should finish
+    test_lazy_mode_2 {
+        distribution: Distribution::Local(2),
+        halt: TerminationType::Lazy,
+        input: [
+            "sleep 1; echo 4; echo 5",
+            "echo 1; echo 2; echo 3; false; echo 6; echo 7",
+            "echo 1",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// eager mode: existing commands can finish, no new commands on any line
+    /// all lines will halt as soon as the current command finishes
+    test_eager_mode {
+        distribution: Distribution::Local(2),
+        halt: TerminationType::Eager,
+        input: [
+            "echo 5; sleep 1; echo 2",
+            "echo 1; echo 2; echo 3; echo 4; false; echo 5",
+            "echo 5",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// never mode: existing lines should finish, new lines should run
+    /// given with explicit command line argument
+    never_mode {
+        distribution: Distribution::Local(2),
+        halt: TerminationType::Never,
+        input: ["sleep 1; echo 4; echo 5", "echo 1; false; echo 1", "echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
@@ -0,0 +1,81 @@
+//! Test 1 remote using 1 thread
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_simple_connection_one_command(),
+        test_simple_connection_serveral_lines(),
+        test_halt_never_implicit(),
+        test_halt_never_explicit(),
+        test_halt_lazy(),
+        test_halt_eager(),
+    ]
+}
+
+pars_test! {
+    test_simple_connection_one_command {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        input: ["echo 1; echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3"],
+    }
+}
+
+pars_test! {
+    test_simple_connection_serveral_lines {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        input: ["echo 1; echo 2; echo 3", "echo 4; echo 5", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_halt_never_implicit {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        input: [
+            "echo 1; echo 2; echo 3; false; echo foo",
+            "false; echo bar",
+            "echo 4; echo 5",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_halt_never_explicit {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        input: [
+            "echo 1; echo 2; echo 3; false; echo foo",
+            "false; echo bar",
+            "echo 4; echo 5",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_halt_lazy {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        halt: TerminationType::Lazy,
+        input: [
+            "echo 1; echo 2; echo 3; false; echo foo",
+            "false; echo bar",
+            "echo 4; echo 5",
+            "\r",
+        ],
+        expect: ["1", "2", "3"],
+    }
+}
+
+pars_test! {
+    /// As this is singled threaded, this should behave the same as halt lazy
+    test_halt_eager {
+        distribution: Distribution::Remote(vec![remote(1)]),
+        halt: TerminationType::Eager,
+        input: ["echo 1; echo 2; echo 3; false; echo foo", "false; echo bar", "\r"],
+        expect: ["1", "2", "3"],
+    }
+}
@@ -0,0 +1,23 @@
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![test_simple_2_threads(), test_simple_3_threads()]
+}
+
+pars_test! {
+    test_simple_2_threads {
+        distribution: Distribution::Remote(vec![remote(2)]),
+        input: ["echo 4; echo 5; sleep 1", "echo 1; echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_simple_3_threads {
+        distribution: Distribution::Remote(vec![remote(3)]),
+        input: ["echo 5; sleep 2", "echo 4; sleep 1", "echo 1; echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
@@ -0,0 +1,63 @@
+//! Test using multiple remotes (including testing )
+//!
+//! # Note
+//! Each test has a stand-in remote of its own (see `harness::remote`), so the suite runs in
+//! parallel like the others. A real remote given with `--host`/`--remote-port` is shared by
+//! every test, so the suite then runs serially, or the load balancing tests would be flaky.
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    let tests = vec![
+        test_simple_2_remotes(),
+        test_remotes_with_errors(),
+        test_load_balance_1(),
+        TestCase::new("test_load_balance_2", test_load_balance_2),
+        TestCase::new("test_load_balance_3", test_load_balance_3),
+    ];
+    if remote(1).is_stand_in() {
+        tests
+    } else {
+        tests.into_iter().map(TestCase::serial).collect()
+    }
+}
+
+pars_test! {
+    test_simple_2_remotes {
+        distribution: Distribution::Remote(vec![remote(1), remote(1)]),
+        input: ["echo 4; echo 5; sleep 2", "echo 1; echo 2; echo 3", "\r"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_remotes_with_errors {
+        distribution: Distribution::Remote(vec![remote(1), remote(1)]),
+        input: [
+            "echo 4; sleep 1.5",
+            "echo 1; echo 2; echo 3; false; echo foo",
+            "echo 5; sleep 3",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    test_load_balance_1 {
+        distribution: Distribution::Remote(vec![remote(1), remote(4)]),
+        halt: TerminationType::Eager,
+        input: [
+            "echo 2; sleep 1",
+            "echo 3; sleep 2",
+            "echo 4; sleep 3",
+            "echo 5; sleep 4",
+            "echo 1",
+            "\r",
+        ],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+fn test_load_balance_2() {}
+fn test_load_balance_3() {}
@@ -0,0 +1,118 @@
+//! Output that is not line-based UTF-8 text must be passed through byte for byte.
+//!
+//! Commands use `printf` octal escapes to print exact bytes, and each test checks the raw
+//! stdout of pars.
+use pars_testkit::{
+    assert::{assert_bytes, assert_lines},
+    bstr::ByteSlice,
+    pars_test, TestCase,
+};
+
+use crate::harness::*;
+
+/// Size of the single-line outputs, bigger than any pipe or read buffer
+const HUGE_LINE_BYTES: usize = 4 * 1024 * 1024;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_latin1(),
+        test_invalid_utf8(),
+        test_nul_bytes(),
+        test_carriage_returns(),
+        test_no_trailing_newline(),
+        test_no_trailing_newline_last(),
+        TestCase::new("test_huge_line", test_huge_line).timeout(30),
+        TestCase::new("test_huge_binary_line", test_huge_binary_line).timeout(30),
+    ]
+}
+
+pars_test! {
+    /// `é` in Latin-1 is the single byte 0xE9, which is not valid UTF-8.
+    /// The line is kept (as `U+FFFD`) in the decoded lines too
+    test_latin1 {
+        distribution: Distribution::Local(1),
+        input: [r"printf 'caf\351\n'", r"echo ok"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"caf\xe9\nok\n");
+            assert_lines(&result.stdout, &["caf\u{FFFD}", "ok"]);
+        },
+    }
+}
+
+pars_test! {
+    test_invalid_utf8 {
+        distribution: Distribution::Local(1),
+        input: [r"printf '\377\376\n'", r"printf '\300\200\n'", r"printf '\342\202\n'"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"\xff\xfe\n\xc0\x80\n\xe2\x82\n");
+        },
+    }
+}
+
+pars_test! {
+    test_nul_bytes {
+        distribution: Distribution::Local(1),
+        input: [r"printf 'a\000b\n'", r"printf '\000\000\n'"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"a\0b\n\0\0\n");
+        },
+    }
+}
+
+pars_test! {
+    test_carriage_returns {
+        distribution: Distribution::Local(1),
+        input: [r"printf 'a\rb\r\n'", r"printf '\r\n'", r"printf 'c\r'"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"a\rb\r\n\r\nc\r");
+        },
+    }
+}
+
+pars_test! {
+    /// a line's output is not given a newline it did not print
+    test_no_trailing_newline {
+        distribution: Distribution::Local(1),
+        input: [r"printf abc", r"echo next", r"printf x; printf y"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"abcnext\nxy");
+        },
+    }
+}
+
+pars_test! {
+    test_no_trailing_newline_last {
+        distribution: Distribution::Local(2),
+        input: [r"printf 'no newline'"],
+        check: |result| {
+            assert_bytes(&result.raw_stdout, b"no newline");
+        },
+    }
+}
+
+/// a single line of text, with no newline, far bigger than any buffer
+fn test_huge_line() {
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds([
+        format!(r"sh -c 'head -c {HUGE_LINE_BYTES} /dev/zero | tr \\000 x'"),
+        "echo".to_string(),
+    ]);
+    let result = program.finish();
+
+    let mut expected = vec![b'x'; HUGE_LINE_BYTES];
+    expected.push(b'\n');
+    assert_bytes(&result.raw_stdout, &expected);
+}
+
+/// a single line of NUL bytes
+fn test_huge_binary_line() {
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds([format!("head -c {HUGE_LINE_BYTES} /dev/zero")]);
+    let result = program.finish();
+
+    assert_eq!(result.raw_stdout.len(), HUGE_LINE_BYTES);
+    assert!(
+        result.raw_stdout.find_not_byteset(b"\0").is_none(),
+        "output was changed"
+    );
+}
@@ -0,0 +1,175 @@
+//! The command line contract of pars: defaults, invalid flags, help output and precedence.
+//!
+//! Arguments are built with [`Argv`] in exactly the order and form given, rather than the
+//! fixed `-J`, `--halt`, `--remote` order `ParsBuilder` uses.
+use pars_testkit::{
+    assert::{assert_clean_error, assert_lines, assert_success},
+    report, RunResult, TestCase,
+};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_defaults", test_defaults),
+        TestCase::new("test_default_halt", test_default_halt),
+        TestCase::new("test_invalid_halt", test_invalid_halt),
+        TestCase::new("test_invalid_jobs", test_invalid_jobs),
+        TestCase::new("test_missing_values", test_missing_values),
+        TestCase::new("test_repeated_jobs", test_repeated_jobs),
+        TestCase::new("test_unknown_flags", test_unknown_flags),
+        TestCase::new("test_flag_order", test_flag_order),
+        TestCase::new("test_help", test_help),
+        TestCase::new("test_version", test_version),
+    ]
+}
+
+/// An argument list for pars, kept in the order it was built.
+#[derive(Debug, Clone, Default)]
+struct Argv(Vec<String>);
+impl Argv {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.0.push(arg.into());
+        self
+    }
+
+    fn jobs(self, jobs: impl ToString) -> Self {
+        self.arg("-J").arg(jobs.to_string())
+    }
+
+    fn halt(self, halt: &str) -> Self {
+        self.arg("--halt").arg(halt)
+    }
+
+    /// Runs pars with these arguments, writing `input` if pars reads it.
+    fn run(&self, input: &[&str]) -> RunResult {
+        report::record("args", self.0.join(" "));
+        let mut program = ParsProgram::from_env()
+            .args(&self.0)
+            .spawn()
+            .expect("Failed to start pars");
+        for line in input {
+            // pars may exit before reading its input, e.g. on an argument error
+            if program.try_write_input(format!("{line}\n")).is_err() {
+                break;
+            }
+        }
+
+        program.finish()
+    }
+}
+
+/// with no flags, lines run one at a time, in order
+fn test_defaults() {
+    let result = Argv::new().run(&["sleep 0.3; echo 1", "echo 2", "echo 3"]);
+    assert_lines(&result.stdout, &["1", "2", "3"]);
+    assert_success(&result);
+}
+
+/// with no `--halt`, pars never halts, running every line despite failures
+fn test_default_halt() {
+    let result = Argv::new().run(&["echo 1", "false", "echo 2"]);
+    assert_lines(&result.stdout, &["1", "2"]);
+}
+
+fn test_invalid_halt() {
+    for halt in ["sometimes", "LAZY", "", "1"] {
+        assert_clean_error(&Argv::new().halt(halt).run(&["echo ran"]));
+    }
+}
+
+fn test_invalid_jobs() {
+    for jobs in ["0", "-1", "two", "1.5", ""] {
+        assert_clean_error(&Argv::new().jobs(jobs).run(&["echo ran"]));
+    }
+}
+
+/// a flag at the end of the arguments, without its value
+fn test_missing_values() {
+    for flag in ["-J", "--halt", "--remote"] {
+        assert_clean_error(&Argv::new().arg(flag).run(&["echo ran"]));
+    }
+}
+
+/// `-J` given twice either fails, or the last one given wins, as for most command lines
+fn test_repeated_jobs() {
+    // the first line is slower, so it prints last only when lines run in parallel
+    let input = ["sleep 0.5; echo slow", "echo fast"];
+    let result = Argv::new().jobs(1).jobs(2).run(&input);
+    if !result.success() {
+        report::record("repeated -J", "rejected");
+        return assert_clean_error(&result);
+    }
+
+    report::record("repeated -J", "last wins");
+    assert_lines(&result.stdout, &["fast", "slow"]);
+}
+
+fn test_unknown_flags() {
+    for flag in ["--frobnicate", "-x", "--jobs-please"] {
+        assert_clean_error(&Argv::new().arg(flag).run(&["echo ran"]));
+    }
+}
+
+/// flags mean the same whichever order they are given in
+fn test_flag_order() {
+    let input = ["sleep 0.5; echo slow", "echo fast; false", "echo never"];
+    let expected = ["fast", "slow"];
+
+    for argv in [
+        Argv::new().jobs(2).halt("lazy"),
+        Argv::new().halt("lazy").jobs(2),
+    ] {
+        let result = argv.run(&input);
+        assert_lines(&result.stdout, &expected);
+    }
+}
+
+/// `--help` prints a usage message mentioning each flag, and exits successfully
+fn test_help() {
+    let result = Argv::new().arg("--help").run(&["echo ran"]);
+    assert_success(&result);
+
+    let help = result.stdout.join("\n");
+    assert!(
+        help.to_lowercase().contains("usage"),
+        "--help did not print a usage message: {help:?}"
+    );
+    for flag in ["-J", "--halt", "--remote"] {
+        assert!(
+            help.contains(flag),
+            "--help did not mention {flag}: {help:?}"
+        );
+    }
+    assert!(
+        !result.stdout.iter().any(|line| line == "ran"),
+        "pars ran its input after printing help"
+    );
+}
+
+/// `--version` prints one line, with the name and version number, and exits successfully
+fn test_version() {
+    let result = Argv::new().arg("--version").run(&["echo ran"]);
+    assert_success(&result);
+
+    let [line] = result.stdout.as_slice() else {
+        panic!(
+            "--version printed {} lines: {:?}",
+            result.stdout.len(),
+            result.stdout
+        );
+    };
+    report::record("version", line);
+    let mut words = line.split_whitespace();
+    let (Some(_name), Some(version)) = (words.next(), words.next()) else {
+        panic!("--version did not print a name and version: {line:?}");
+    };
+    assert!(
+        version.split('.').all(|part| part.parse::<u32>().is_ok()),
+        "--version did not print a version number: {line:?}"
+    );
+}
@@ -0,0 +1,82 @@
+//! Checks `-J N` really bounds how many lines pars runs at once.
+//!
+//! pars is sampled through `/proc` while it runs, counting its threads and the commands it
+//! has running as child processes. It must never have more than `N` children at once. The
+//! peak thread and child counts are recorded in the report.
+use std::time::Duration;
+
+use pars_testkit::{assert::assert_lines_unordered, report, RunResult, TestCase};
+
+use crate::harness::*;
+
+/// How often pars' threads and children are counted
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(5);
+
+pub fn tests() -> Vec<TestCase> {
+    let mut tests = Vec::new();
+    for threads in [1, 2, 4, 8] {
+        tests.push(TestCase::new(
+            format!("test_sleeps/j{threads}"),
+            move || test_sleeps(threads),
+        ));
+        tests.push(TestCase::new(
+            format!("test_sequences/j{threads}"),
+            move || test_sequences(threads),
+        ));
+    }
+
+    tests
+}
+
+/// Runs `input` with `-J threads`, sampling pars throughout.
+fn run_monitored(threads: u32, input: &[String]) -> RunResult {
+    let mut program = ParsProgram::from_env()
+        .distribution(Distribution::Local(threads))
+        .monitor(SAMPLE_INTERVAL)
+        .spawn()
+        .expect("Failed to start pars");
+    program.run_cmds(input);
+    program.finish()
+}
+
+/// Records the peak thread and child counts, and checks there were at most `threads`
+/// children, and that more than one ran at once when allowed.
+fn check_children(result: &RunResult, threads: u32) {
+    let stats = result.proc_stats.as_ref().expect("pars was not monitored");
+    report::record("peak threads", stats.peak_threads);
+    report::record("peak children", stats.peak_children);
+
+    assert!(
+        stats.peak_children <= threads as usize,
+        "pars ran {} commands at once with -J {threads}",
+        stats.peak_children
+    );
+    // every line takes long enough that all threads should have been seen busy together
+    assert!(
+        threads == 1 || stats.peak_children > 1,
+        "pars never ran more than one command at once with -J {threads}"
+    );
+}
+
+/// more lines than threads, each running a single slow command
+fn test_sleeps(threads: u32) {
+    let lines = 3 * threads as usize;
+    let input: Vec<_> = (0..lines).map(|_| "sleep 0.3".to_string()).collect();
+
+    let result = run_monitored(threads, &input);
+    assert!(result.success(), "pars failed with {}", result.exit());
+    check_children(&result, threads);
+}
+
+/// more lines than threads, each running several commands one after another
+fn test_sequences(threads: u32) {
+    let lines = 2 * threads as usize;
+    let input: Vec<_> = (0..lines)
+        .map(|i| format!("sleep 0.2; echo {i}; sleep 0.2"))
+        .collect();
+    let expected: Vec<_> = (0..lines).map(|i| i.to_string()).collect();
+
+    let result = run_monitored(threads, &input);
+    assert_lines_unordered(&result.stdout, &expected);
+    check_children(&result, threads);
+}
@@ -0,0 +1,188 @@
+//! Checks commands see the environment and working directory pars was started with, and
+//! what remote commands see instead.
+//!
+//! A local command inherits pars' variables and working directory. ssh passes on neither, so
+//! a remote command gets none of the caller's variables, and starts in the remote user's home
+//! directory, where its relative paths are resolved.
+//!
+//! Commands are not run through a shell (see `test_shell_semantics`), so variables are read
+//! with `sh -c 'echo $VAR'` and `printenv`.
+use std::{env, fs};
+
+use pars_testkit::{assert::assert_lines, report, ParsBuilder, TempDir, TestCase, BINARY_VAR};
+
+use crate::harness::*;
+
+/// A variable set for pars by these tests
+const VAR: &str = "PARS_AUTOTEST_VAR";
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_env_clear/local", test_env_clear),
+        TestCase::new("test_env_var/local", test_env_var),
+        TestCase::new("test_pwd/local", test_pwd),
+        TestCase::new("test_relative_read/local", test_relative_read),
+        TestCase::new("test_relative_write/local", test_relative_write),
+        TestCase::new("test_env_var/remote", test_env_var_remote),
+        TestCase::new("test_pwd/remote", test_pwd_remote),
+        TestCase::new("test_relative_read/remote", test_relative_read_remote),
+        TestCase::new("test_relative_write/remote", test_relative_write_remote),
+    ]
+}
+
+/// Runs `input` with pars, on one thread so output keeps the order of the input, configured
+/// by `setup`, and returns what it printed.
+fn run(
+    distribution: Distribution,
+    setup: impl FnOnce(ParsBuilder) -> ParsBuilder,
+    input: &[&str],
+) -> Vec<String> {
+    let builder = ParsProgram::from_env().distribution(distribution);
+    let mut program = setup(builder).spawn().expect("Failed to start pars");
+    program.run_cmds(input);
+    let result = program.finish();
+    assert!(
+        result.success(),
+        "pars failed with {}, printing {:?}",
+        result.exit(),
+        result.stderr
+    );
+
+    result.stdout
+}
+
+/// a variable set for pars is seen by its commands
+fn test_env_var() {
+    let output = run(
+        Distribution::Local(1),
+        |pars| pars.env(VAR, "hello world"),
+        &[&format!("sh -c 'echo ${VAR}'"), &format!("printenv {VAR}")],
+    );
+    assert_lines(&output, &["hello world", "hello world"]);
+}
+
+/// commands see nothing of the caller's environment when pars' is cleared.
+/// Only local, as ssh needs `HOME` to find its key.
+fn test_env_clear() {
+    let path = env::var_os("PATH").unwrap_or_default();
+    let output = run(
+        Distribution::Local(1),
+        |pars| pars.env_clear().env("PATH", path).env(VAR, "1"),
+        &["sh -c 'echo ${HOME-unset}'", &format!("printenv {VAR}")],
+    );
+    assert_lines(&output, &["unset", "1"]);
+}
+
+/// commands start in pars' working directory
+fn test_pwd() {
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+    let output = run(
+        Distribution::Local(1),
+        |pars| pars.current_dir(dir.path()),
+        &["pwd"],
+    );
+    assert_lines(&output, &[dir.path().display().to_string()]);
+}
+
+/// relative paths are read from pars' working directory
+fn test_relative_read() {
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+    fs::write(dir.join("data.txt"), "contents\n").unwrap();
+    fs::create_dir(dir.join("sub")).unwrap();
+    fs::write(dir.join("sub/more.txt"), "more contents\n").unwrap();
+
+    let output = run(
+        Distribution::Local(1),
+        |pars| pars.current_dir(dir.path()),
+        &["cat data.txt", "cat sub/more.txt", "ls"],
+    );
+    assert_lines(&output, &["contents", "more contents", "data.txt", "sub"]);
+}
+
+/// relative paths are written in pars' working directory
+fn test_relative_write() {
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+    fs::create_dir(dir.join("sub")).unwrap();
+
+    run(
+        Distribution::Local(1),
+        |pars| pars.current_dir(dir.path()),
+        &["touch created.txt", "sh -c 'echo written > sub/out.txt'"],
+    );
+    assert!(
+        dir.join("created.txt").exists(),
+        "created.txt was not created in pars' working directory"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("sub/out.txt")).ok().as_deref(),
+        Some("written\n"),
+        "sub/out.txt was not written in pars' working directory"
+    );
+}
+
+/// neither a variable set for pars nor one it inherits, such as the `PARS_BINARY` set by the
+/// runner, is passed on to remote commands, as ssh does not send them
+fn test_env_var_remote() {
+    let output = run(
+        Distribution::Remote(vec![remote(1)]),
+        |pars| pars.env(VAR, "hello world"),
+        &[
+            &format!("sh -c 'echo ${{{VAR}-unset}}'"),
+            &format!("sh -c 'echo ${{{BINARY_VAR}-unset}}'"),
+        ],
+    );
+    assert_lines(&output, &["unset", "unset"]);
+}
+
+/// remote commands start in the remote's home directory, not pars' working directory
+fn test_pwd_remote() {
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+    let output = run(
+        Distribution::Remote(vec![remote(1)]),
+        |pars| pars.current_dir(dir.path()),
+        &["pwd", "sh -c 'cd && pwd'"],
+    );
+    let [pwd, home] = &output[..] else {
+        panic!("expected the remote's working directory and home, got {output:?}");
+    };
+    report::record("remote pwd", pwd);
+    assert_eq!(
+        pwd, home,
+        "remote commands did not start in the remote's home"
+    );
+}
+
+/// relative paths are read from the remote's home directory
+fn test_relative_read_remote() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    fs::write(stand_in.home().join("data.txt"), "remote contents\n").unwrap();
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+    fs::write(dir.join("data.txt"), "local contents\n").unwrap();
+
+    let output = run(
+        Distribution::Remote(vec![stand_in.remote(1)]),
+        |pars| stand_in.setup(pars).current_dir(dir.path()),
+        &["cat data.txt"],
+    );
+    assert_lines(&output, &["remote contents"]);
+}
+
+/// relative paths are written in the remote's home directory
+fn test_relative_write_remote() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let dir = TempDir::new("pars-autotest-env").unwrap();
+
+    run(
+        Distribution::Remote(vec![stand_in.remote(1)]),
+        |pars| stand_in.setup(pars).current_dir(dir.path()),
+        &["touch created.txt"],
+    );
+    assert!(
+        stand_in.home().join("created.txt").exists(),
+        "created.txt was not created in the remote's home directory"
+    );
+    assert!(
+        !dir.join("created.txt").exists(),
+        "created.txt was created in pars' local working directory"
+    );
+}
@@ -0,0 +1,61 @@
+//! The exit status of pars, under every halt mode.
+//!
+//! # Convention
+//! These tests pin down the following convention:
+//! - pars exits with `0` if every command it ran succeeded (including when given no input).
+//! - pars exits with `1` if any command failed, whether it exited non-zero, could not be
+//!   started, or was killed by a signal.
+//! - pars itself is never killed by a signal because one of its commands was.
+//!
+//! The status does not depend on the halt mode: halting only changes which commands run.
+use pars_testkit::{assert::assert_exit, Exit, Matrix, TestCase};
+
+use crate::harness::*;
+
+/// pars' status when every command succeeded
+const SUCCESS: Exit = Exit::Code(0);
+/// pars' status when any command failed
+const FAILURE: Exit = Exit::Code(1);
+
+pub fn tests() -> Vec<TestCase> {
+    let matrix = Matrix::new()
+        .local(1..=2)
+        .default_halt()
+        .halts(TerminationType::ALL);
+
+    let cases: &[(&str, &[&str], Exit)] = &[
+        ("no_input", &[], SUCCESS),
+        ("all_succeed", &["echo 1", "true; echo 2", "true"], SUCCESS),
+        ("first_fails", &["false", "echo 1", "true"], FAILURE),
+        (
+            "some_fail",
+            &["echo 1", "true; false; echo 2", "echo 3"],
+            FAILURE,
+        ),
+        ("last_fails", &["echo 1", "true", "echo 2; false"], FAILURE),
+        (
+            "not_found",
+            &["echo 1", "pars-autotest-no-such-command"],
+            FAILURE,
+        ),
+        (
+            "killed_by_signal",
+            &["echo 1", "sh -c 'kill -KILL $$'"],
+            FAILURE,
+        ),
+        (
+            "killed_then_succeed",
+            &["sh -c 'kill -TERM $$'; true", "true"],
+            FAILURE,
+        ),
+    ];
+
+    cases
+        .iter()
+        .flat_map(|&(name, input, expected)| {
+            matrix.expand(name, input, move |_, result| {
+                assert_exit(result, expected);
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,86 @@
+//! Checks pars does not busy-wait while it has nothing to do.
+//!
+//! A worker thread spinning on a channel or lock while it waits for input shows up as CPU
+//! time used while pars should be idle. Each test leaves pars waiting, either for input or
+//! for slow commands, and measures the CPU time it used (not counting its children) through
+//! `/proc/<pid>/stat`.
+use std::{thread, time::Duration};
+
+use pars_testkit::{cpu_time, report, TestCase};
+
+use crate::harness::*;
+
+/// Most CPU pars may use while waiting, as a fraction of one core
+const MAX_IDLE_CPU: f64 = 0.05;
+/// Time given to pars to start up (and connect to remotes) before measuring
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+/// How long pars' CPU time is measured for
+const IDLE_TIME: Duration = Duration::from_secs(3);
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_idle_no_input", test_idle_no_input),
+        TestCase::new("test_idle_after_lines", test_idle_after_lines),
+        TestCase::new("test_waiting_for_slow_lines", test_waiting_for_slow_lines),
+        TestCase::new("test_waiting_for_slow_remote", test_waiting_for_slow_remote),
+    ]
+}
+
+/// Measures the CPU pars uses over [`IDLE_TIME`], and checks it is below [`MAX_IDLE_CPU`].
+fn check_idle(program: &ParsProgram, waiting_for: &str) {
+    let pid = program.id();
+    thread::sleep(SETTLE_TIME);
+    let before = cpu_time(pid).expect("pars exited early");
+    thread::sleep(IDLE_TIME);
+    let after = cpu_time(pid).expect("pars exited early");
+
+    let usage = (after - before).as_secs_f64() / IDLE_TIME.as_secs_f64();
+    report::record("idle cpu", format!("{:.1}%", usage * 100.0));
+    assert!(
+        usage <= MAX_IDLE_CPU,
+        "pars used {:.1}% of a CPU while waiting for {waiting_for}, more than the limit of {:.1}%",
+        usage * 100.0,
+        MAX_IDLE_CPU * 100.0
+    );
+}
+
+/// pars with stdin open but nothing written to it
+fn test_idle_no_input() {
+    let mut program = launch(Distribution::Local(8), None);
+    check_idle(&program, "input");
+
+    let result = program.finish();
+    assert!(
+        result.stdout.is_empty(),
+        "unexpected output {:?}",
+        result.stdout
+    );
+}
+
+/// pars after every thread has run a line, with stdin still open
+fn test_idle_after_lines() {
+    let mut program = launch(Distribution::Local(8), None);
+    program.run_cmds((0..8).map(|i| format!("echo {i}")));
+    check_idle(&program, "more input");
+
+    assert_eq!(program.finish().stdout.len(), 8);
+}
+
+/// pars with every thread waiting on a command that takes a while
+fn test_waiting_for_slow_lines() {
+    let mut program = launch(Distribution::Local(8), None);
+    program.run_cmds((0..8).map(|i| format!("sleep 6; echo {i}")));
+    check_idle(&program, "slow lines");
+
+    assert_eq!(program.finish().stdout.len(), 8);
+}
+
+/// pars with every remote thread waiting on a command that takes a while
+fn test_waiting_for_slow_remote() {
+    let remote = remote(8);
+    let mut program = launch(Distribution::Remote(vec![remote]), None);
+    program.run_cmds((0..8).map(|i| format!("sleep 6; echo {i}")));
+    check_idle(&program, "a slow remote");
+
+    assert_eq!(program.finish().stdout.len(), 8);
+}
@@ -0,0 +1,112 @@
+//! Scenarios declared once and run across every distribution and halt mode.
+//!
+//! The expected output of each scenario is worked out from the number of lines pars can run
+//! at once, and how eagerly it halts.
+use pars_testkit::{
+    assert::{assert_lines, assert_lines_unordered},
+    Matrix, TestCase,
+};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    let matrix = matrix();
+    let mut tests = Vec::new();
+    tests.extend(all_succeed(&matrix));
+    tests.extend(error_in_first_line(&matrix));
+    tests.extend(error_while_running(&matrix));
+
+    tests
+}
+
+/// Local with 1 to 3 threads, one remote with 1 to 3 threads, and two remotes,
+/// each with and without every halt mode
+fn matrix() -> Matrix {
+    Matrix::new()
+        .local(1..=3)
+        .distribution(Distribution::Remote(vec![remote(1)]))
+        .distribution(Distribution::Remote(vec![remote(2)]))
+        .distribution(Distribution::Remote(vec![remote(3)]))
+        .distribution(Distribution::Remote(vec![remote(1), remote(1)]))
+        .default_halt()
+        .halts(TerminationType::ALL)
+}
+
+/// without errors, every line is printed; in order only if they run one at a time
+fn all_succeed(matrix: &Matrix) -> Vec<TestCase> {
+    matrix.expand(
+        "all_succeed",
+        &["echo 1", "echo 2; echo 3", "echo 4", "\r"],
+        |cell, result| {
+            let expected = ["1", "2", "3", "4"];
+            if cell.distribution.total_threads() == 1 {
+                assert_lines(&result.stdout, &expected);
+            } else {
+                assert_lines_unordered(&result.stdout, &expected);
+            }
+        },
+    )
+}
+
+/// the first line fails after a second, by which time the second line has already run if
+/// there was a thread free for it:
+/// - never: the second line runs either way
+/// - lazy and eager: the second line only runs if it was started before the error
+fn error_in_first_line(matrix: &Matrix) -> Vec<TestCase> {
+    matrix.expand(
+        "error_in_first_line",
+        &["sleep 1; echo 1; false; echo hidden", "echo 2", "\r"],
+        |cell, result| {
+            let parallel = cell.distribution.total_threads() > 1;
+            if parallel {
+                assert_lines_unordered(&result.stdout, &["1", "2"]);
+            } else if cell.effective_halt() == TerminationType::Never {
+                assert_lines(&result.stdout, &["1", "2"]);
+            } else {
+                assert_lines(&result.stdout, &["1"]);
+            }
+        },
+    )
+}
+
+/// a line fails while another is part way through, with a third line still to run:
+/// - never: every line finishes
+/// - lazy: lines already running finish, but no more are started
+/// - eager: lines still running are stopped after their current command
+///
+/// With one thread nothing is running beside the failing line, and with three the last line
+/// has already run by the time it fails.
+fn error_while_running(matrix: &Matrix) -> Vec<TestCase> {
+    matrix.expand(
+        "error_while_running",
+        &[
+            "echo 1; sleep 2; echo 2",
+            "sleep 1; echo 3; false; echo hidden",
+            "echo 4",
+            "\r",
+        ],
+        |cell, result| {
+            let threads = cell.distribution.total_threads();
+            let first_finished = threads == 1;
+            let last_started = threads >= 3;
+            let mut expected = vec!["1"];
+            match cell.effective_halt() {
+                TerminationType::Never => expected.extend(["2", "3", "4"]),
+                TerminationType::Lazy => {
+                    expected.extend(["2", "3"]);
+                    expected.extend(last_started.then_some("4"));
+                }
+                TerminationType::Eager => {
+                    expected.extend(first_finished.then_some("2"));
+                    expected.push("3");
+                    expected.extend(last_started.then_some("4"));
+                }
+            }
+            if threads == 1 {
+                assert_lines(&result.stdout, &expected);
+            } else {
+                assert_lines_unordered(&result.stdout, &expected);
+            }
+        },
+    )
+}
@@ -0,0 +1,168 @@
+//! Local threads and remotes at once (`-J` alongside `--remote`), which should share the work.
+//!
+//! The remotes are ports of one stand-in, whose commands are given the path of their remote's
+//! execution log in `STANDIN_LOG`, while local commands have no such variable. So each line
+//! prints its name and where it ran, and the tests check every executor took a share.
+use std::collections::BTreeMap;
+
+use pars_testkit::{assert::assert_success, report, RunResult, TestCase};
+
+use crate::harness::*;
+
+/// How long each line runs, long enough that every executor is busy at once
+const LINE_SECS: f64 = 0.5;
+
+/// Seconds each test may take, well beyond what the lines need
+const TIMEOUT: u64 = 30;
+
+pub fn tests() -> Vec<TestCase> {
+    let mut tests: Vec<_> = SHARED
+        .iter()
+        .map(|&(label, local, remotes)| {
+            TestCase::new(format!("test_work_shared/{label}"), move || {
+                test_work_shared(local, remotes)
+            })
+            .timeout(TIMEOUT)
+        })
+        .collect();
+    tests.extend([
+        TestCase::new("test_localhost_remote", test_localhost_remote).timeout(TIMEOUT),
+        TestCase::new("test_remote_before_jobs", test_remote_before_jobs).timeout(TIMEOUT),
+        TestCase::new("test_every_line_once", test_every_line_once).timeout(TIMEOUT),
+    ]);
+
+    tests
+}
+
+/// Local threads, and the threads of each remote, that work should be shared between
+const SHARED: &[(&str, u32, &[u32])] = &[
+    ("j1+r1", 1, &[1]),
+    ("j2+r2", 2, &[2]),
+    ("j1+r1+1", 1, &[1, 1]),
+];
+
+/// A line named `name` that runs for `secs`, then prints its name and where it ran: `local`,
+/// or the file name of its remote's execution log
+fn line(name: &str, secs: f64) -> String {
+    format!(r#"sh -c 'sleep {secs}; echo {name} $(basename "${{STANDIN_LOG:-local}}")'"#)
+}
+
+/// How many lines ran on each executor, by where they said they ran
+fn executors(result: &RunResult) -> BTreeMap<String, usize> {
+    let mut executors = BTreeMap::new();
+    for output in &result.stdout {
+        let (_, place) = output
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("malformed output line {output:?}"));
+        *executors.entry(place.to_string()).or_default() += 1;
+    }
+
+    executors
+}
+
+/// Runs twice as many lines as can run at once, and returns the result
+fn run(stand_in: &StandIn, distribution: Distribution) -> RunResult {
+    let count = 2 * distribution.total_threads();
+    let input: Vec<_> = (0..count)
+        .map(|i| line(&format!("l{i}"), LINE_SECS))
+        .collect();
+
+    let mut program = launch_with(stand_in, distribution, None);
+    program.run_cmds(&input);
+    let result = program.finish();
+    assert_success(&result);
+    assert_eq!(
+        result.stdout.len(),
+        count as usize,
+        "expected a line of output from each of the {count} lines: {:?}",
+        result.stdout
+    );
+
+    result
+}
+
+/// Asserts local threads and each of `remotes` remotes ran at least one line
+fn assert_shared(result: &RunResult, remotes: usize) {
+    let executors = executors(result);
+    report::record("lines run", format!("{executors:?}"));
+
+    let expected = ["local".to_string()]
+        .into_iter()
+        .chain((0..remotes).map(|i| format!("remote-{i}.log")));
+    for executor in expected {
+        assert!(
+            executors.contains_key(&executor),
+            "{executor} ran none of the lines, which ran on {executors:?}"
+        );
+    }
+}
+
+/// `local` threads and a remote on its own port for each of `remotes`
+fn test_work_shared(local: u32, remotes: &[u32]) {
+    let stand_in = stand_in()
+        .ports(remotes.len())
+        .start()
+        .expect("Failed to start stand-in");
+    let remote_hosts = remotes
+        .iter()
+        .enumerate()
+        .map(|(i, &threads)| stand_in.remote_on(i, threads))
+        .collect();
+
+    let result = run(&stand_in, Distribution::Mixed(local, remote_hosts));
+    assert_shared(&result, remotes.len());
+}
+
+/// a remote that is this machine, named `localhost`, is still separate from the local threads
+fn test_localhost_remote() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let remote = RemoteHost::new("localhost", stand_in.addr().port(), 2);
+
+    let result = run(&stand_in, Distribution::Mixed(2, vec![remote]));
+    assert_shared(&result, 1);
+}
+
+/// `--remote` given before `-J` means the same as after it
+fn test_remote_before_jobs() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let remote = stand_in.remote(1).to_string();
+    let input: Vec<_> = (0..4).map(|i| line(&format!("l{i}"), LINE_SECS)).collect();
+
+    let builder = ParsProgram::from_env().args(["--remote", &remote, "-J", "1"]);
+    let mut program = stand_in
+        .setup(builder)
+        .spawn()
+        .expect("Failed to start pars");
+    program.run_cmds(&input);
+    let result = program.finish();
+
+    assert_success(&result);
+    assert_shared(&result, 1);
+}
+
+/// with many short lines, each runs exactly once, wherever it runs
+fn test_every_line_once() {
+    let stand_in = stand_in()
+        .ports(2)
+        .start()
+        .expect("Failed to start stand-in");
+    let distribution =
+        Distribution::Mixed(2, vec![stand_in.remote_on(0, 2), stand_in.remote_on(1, 1)]);
+    let input: Vec<_> = (0..40).map(|i| line(&format!("l{i}"), 0.0)).collect();
+
+    let mut program = launch_with(&stand_in, distribution, None);
+    program.run_cmds(&input);
+    let result = program.finish();
+    assert_success(&result);
+    report::record("lines run", format!("{:?}", executors(&result)));
+
+    let mut names: Vec<_> = result
+        .stdout
+        .iter()
+        .filter_map(|output| output.split(' ').next())
+        .collect();
+    names.sort_unstable();
+    let mut expected: Vec<_> = (0..40).map(|i| format!("l{i}")).collect();
+    expected.sort_unstable();
+    assert_eq!(names, expected, "lines were lost or run more than once");
+}
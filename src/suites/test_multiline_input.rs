@@ -0,0 +1,127 @@
+//! Input that is empty, blank, or looks like it spans several lines.
+//!
+//! pars reads its input one line at a time, and every line is run on its own: there is no
+//! heredoc or line continuation support, as lines are never given to a shell.
+use pars_testkit::{
+    assert::{assert_lines, assert_success},
+    pars_test, TestCase,
+};
+
+use crate::harness::*;
+
+/// Size of the line used to check long input is read in full
+const LONG_LINE_BYTES: usize = 1024 * 1024 + 1;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_no_input", test_no_input),
+        TestCase::new("test_whitespace_only", test_whitespace_only),
+        TestCase::new("test_no_trailing_newline", test_no_trailing_newline),
+        test_heredoc_not_supported(),
+        test_heredoc_quoted_delimiter(),
+        TestCase::new("test_backslash_continuation", test_backslash_continuation),
+        TestCase::new("test_long_line", test_long_line).timeout(30),
+        TestCase::new("test_long_lines_parallel", test_long_lines_parallel).timeout(30),
+    ]
+}
+
+/// stdin is closed without anything being written
+fn test_no_input() {
+    let mut program = launch(Distribution::Local(2), None);
+    let result = program.finish();
+
+    assert_lines::<&str>(&result.stdout, &[]);
+    assert_success(&result);
+}
+
+/// blank lines and lines of only spaces and tabs contain no commands
+fn test_whitespace_only() {
+    let mut program = launch(Distribution::Local(2), None);
+    program.write_input("\n   \n\t\n \t \n\n");
+    let result = program.finish();
+
+    assert_lines::<&str>(&result.stdout, &[]);
+    assert_success(&result);
+}
+
+/// the last line of input is still run if it does not end with a newline
+fn test_no_trailing_newline() {
+    let mut program = launch(Distribution::Local(1), None);
+    program.write_input("echo 1\necho 2");
+    let result = program.finish();
+
+    assert_lines(&result.stdout, &["1", "2"]);
+}
+
+pars_test! {
+    /// `<<` is passed to `cat` as a file name, and the body lines are run as commands
+    /// (which don't exist), so nothing is printed
+    test_heredoc_not_supported {
+        distribution: Distribution::Local(1),
+        input: [r"cat << EOF", r"hello", r"EOF", r"echo after"],
+        expect: ["after"],
+    }
+}
+
+pars_test! {
+    /// the quoted delimiter is also a file name to `cat`, which fails, so the rest of
+    /// its line is skipped
+    test_heredoc_quoted_delimiter {
+        distribution: Distribution::Local(1),
+        input: [r"cat <<'EOF'; echo same line", r"echo next line", r"EOF"],
+        expect: ["next line"],
+    }
+}
+
+/// a trailing backslash does not join a line with the next.
+/// pars may reject the first line (an unfinished escape), but must still run the second
+fn test_backslash_continuation() {
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds([r"echo a \", r"echo b"]);
+    let result = program.finish();
+
+    assert!(
+        !result.stdout.iter().any(|line| line.contains("a b")),
+        "lines were joined: {:?}",
+        result.stdout
+    );
+    assert_eq!(result.stdout.last().map(String::as_str), Some("b"));
+}
+
+/// A line of `echo` with enough arguments to be over `LONG_LINE_BYTES` long.
+///
+/// Linux limits a single argument to 128KiB, so the text is split into many arguments.
+fn long_line(word: char) -> (String, String) {
+    let arg = word.to_string().repeat(1023);
+    let args = vec![arg; LONG_LINE_BYTES / 1024 + 1].join(" ");
+
+    (format!("echo {args}"), args)
+}
+
+/// a line over 1MiB long is read and printed in full
+fn test_long_line() {
+    let (line, expected) = long_line('a');
+
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds([line.as_str(), "echo done"]);
+    let result = program.finish();
+
+    assert_eq!(result.stdout.len(), 2, "expected the long line and `done`");
+    assert!(result.stdout[0].len() >= LONG_LINE_BYTES);
+    assert!(result.stdout[0] == expected, "long line was changed");
+    assert_eq!(result.stdout[1], "done");
+}
+
+/// several long lines running at once each come out whole
+fn test_long_lines_parallel() {
+    let lines: Vec<_> = ['a', 'b', 'c', 'd'].into_iter().map(long_line).collect();
+
+    let mut program = launch(Distribution::Local(4), None);
+    program.run_cmds(lines.iter().map(|(line, _)| line));
+    let mut result = program.finish();
+
+    let mut expected: Vec<_> = lines.into_iter().map(|(_, output)| output).collect();
+    result.stdout.sort_unstable();
+    expected.sort_unstable();
+    assert!(result.stdout == expected, "long lines were changed");
+}
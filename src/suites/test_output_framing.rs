@@ -0,0 +1,156 @@
+//! Output from several remotes at once, which pars reads from separate network streams.
+//!
+//! Each test runs against a stand-in that fragments its output (see
+//! `StandInBuilder::fragment`), so lines reach pars split at arbitrary points, in pieces that
+//! arrive separately. Every line's output starts with a tag naming it, and the raw stdout of
+//! pars must be each line's output in full, one after another, never interleaved.
+use pars_testkit::{
+    assert::{assert_blocks, assert_success},
+    TestCase,
+};
+
+use crate::harness::*;
+
+/// Largest piece of output the stand-in sends at once
+const FRAGMENT_BYTES: usize = 13;
+
+/// Size of each long line, much bigger than any piece the stand-in sends
+const LONG_LINE_BYTES: usize = 16 * 1024;
+
+/// Seconds each test may take, well beyond what the lines need
+const TIMEOUT: u64 = 30;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_long_lines", test_long_lines).timeout(TIMEOUT),
+        TestCase::new("test_partial_lines", test_partial_lines).timeout(TIMEOUT),
+        TestCase::new("test_no_trailing_newline", test_no_trailing_newline).timeout(TIMEOUT),
+        TestCase::new("test_several_lines", test_several_lines).timeout(TIMEOUT),
+        TestCase::new("test_everything_at_once", test_everything_at_once).timeout(TIMEOUT),
+    ]
+}
+
+/// A line for pars, and exactly what it should print
+struct Line {
+    command: String,
+    output: Vec<u8>,
+}
+impl Line {
+    /// One line of `LONG_LINE_BYTES` copies of `fill`, made in a shell as pars does not
+    /// run pipelines itself
+    fn long(tag: &str, fill: char) -> Self {
+        let mut output = format!("{tag}:").into_bytes();
+        output.extend(fill.to_string().repeat(LONG_LINE_BYTES).bytes());
+        output.push(b'\n');
+        Self {
+            command: format!(
+                r"printf '{tag}:'; sh -c 'head -c {LONG_LINE_BYTES} /dev/zero | tr \\000 {fill}'; echo"
+            ),
+            output,
+        }
+    }
+
+    /// A line printed in two halves, `pause` seconds apart
+    fn partial(tag: &str, pause: f64) -> Self {
+        Self {
+            command: format!("printf '{tag}: first half'; sleep {pause}; printf ' second half\\n'"),
+            output: format!("{tag}: first half second half\n").into_bytes(),
+        }
+    }
+
+    /// Output that does not end in a newline, printed after `pause` seconds
+    fn unterminated(tag: &str, pause: f64) -> Self {
+        Self {
+            command: format!("sleep {pause}; printf '{tag}: no newline'"),
+            output: format!("{tag}: no newline").into_bytes(),
+        }
+    }
+
+    /// Three lines, `pause` seconds apart, which should stay together
+    fn several(tag: &str, pause: f64) -> Self {
+        Self {
+            command: format!(
+                "echo '{tag}: one'; sleep {pause}; echo '{tag}: two'; sleep {pause}; echo '{tag}: three'"
+            ),
+            output: format!("{tag}: one\n{tag}: two\n{tag}: three\n").into_bytes(),
+        }
+    }
+}
+
+/// Runs `lines` across three single threaded remotes on a fragmenting stand-in, and checks
+/// each line's output came out whole
+fn run(lines: &[Line]) {
+    let stand_in = stand_in()
+        .fragment(FRAGMENT_BYTES)
+        .start()
+        .expect("Failed to start stand-in");
+    let distribution = Distribution::Remote(vec![stand_in.remote(1); 3]);
+
+    let mut program = launch_with(&stand_in, distribution, None);
+    program.run_cmds(lines.iter().map(|line| line.command.as_str()));
+    let result = program.finish();
+
+    let outputs: Vec<_> = lines.iter().map(|line| &line.output).collect();
+    assert_blocks(&result.raw_stdout, &outputs);
+    assert_success(&result);
+}
+
+fn test_long_lines() {
+    run(&[
+        Line::long("l1", 'a'),
+        Line::long("l2", 'b'),
+        Line::long("l3", 'c'),
+        Line::long("l4", 'd'),
+        Line::long("l5", 'e'),
+        Line::long("l6", 'f'),
+    ]);
+}
+
+/// the halves of each line are sent at overlapping times on different remotes
+fn test_partial_lines() {
+    run(&[
+        Line::partial("p1", 0.3),
+        Line::partial("p2", 0.2),
+        Line::partial("p3", 0.1),
+        Line::partial("p4", 0.3),
+        Line::partial("p5", 0.1),
+        Line::partial("p6", 0.2),
+    ]);
+}
+
+/// output without a newline is not given one, nor joined up with the next line's
+fn test_no_trailing_newline() {
+    run(&[
+        Line::unterminated("n1", 0.2),
+        Line::unterminated("n2", 0.1),
+        Line::unterminated("n3", 0.0),
+        Line::several("s1", 0.1),
+        Line::unterminated("n4", 0.1),
+        Line::unterminated("n5", 0.2),
+    ]);
+}
+
+fn test_several_lines() {
+    run(&[
+        Line::several("s1", 0.1),
+        Line::several("s2", 0.05),
+        Line::several("s3", 0.15),
+        Line::several("s4", 0.1),
+        Line::several("s5", 0.05),
+        Line::several("s6", 0.0),
+    ]);
+}
+
+fn test_everything_at_once() {
+    run(&[
+        Line::long("l1", 'a'),
+        Line::partial("p1", 0.2),
+        Line::unterminated("n1", 0.1),
+        Line::several("s1", 0.1),
+        Line::long("l2", 'b'),
+        Line::partial("p2", 0.1),
+        Line::several("s2", 0.05),
+        Line::unterminated("n2", 0.2),
+        Line::long("l3", 'c'),
+    ]);
+}
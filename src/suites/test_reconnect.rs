@@ -0,0 +1,264 @@
+//! What pars does when a connection to a remote drops: at startup, during a line, or between
+//! lines.
+//!
+//! There is no one right answer: pars might reconnect, re-run the line elsewhere, lose it, or
+//! give up. So by default each test only records what pars did (its [`Outcome`]), failing only
+//! if pars hangs. Given `--reconnect-policy`, each test instead asserts pars did that.
+//!
+//! Each test runs its lines on two remotes, ports of one stand-in, the first of which drops a
+//! connection once (see `Disconnect`). Every line logs its start and end to the execution log
+//! of the remote it runs on, where the stand-in also logs the drop, so the tests see which
+//! line was cut off and where it was run again.
+use std::{collections::BTreeMap, fmt, fs, str::FromStr, time::Duration};
+
+use pars_testkit::{libc, report, Disconnect, Exit, TestCase};
+
+use crate::harness::*;
+
+/// How long each line runs
+const LINE_SECS: f64 = 0.3;
+
+/// Lines given to pars in each test
+const LINES: usize = 6;
+
+/// How long pars may take to finish once its input has ended, before it is taken to have hung
+const HANG_AFTER: Duration = Duration::from_secs(15);
+
+/// Seconds each test may take, well beyond what the lines need
+const TIMEOUT: u64 = 30;
+
+pub fn tests() -> Vec<TestCase> {
+    [
+        Disconnect::AtStartup,
+        Disconnect::DuringLine,
+        Disconnect::BetweenLines,
+    ]
+    .into_iter()
+    .map(|when| {
+        TestCase::new(format!("test_drop_{}", when.as_str()), move || {
+            test_drop(when)
+        })
+        .timeout(TIMEOUT)
+    })
+    .collect()
+}
+
+/// What pars did about a dropped connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every line printed its output once and pars succeeded, no line needing to be re-run
+    Recovered,
+    /// Every line printed its output once, the line cut off having been run again, exactly
+    /// once, on the other remote
+    RerunElsewhere,
+    /// As for `RerunElsewhere`, but on the remote that dropped the connection
+    RerunSame,
+    /// Some line never printed its output, or pars failed though no line did, having run
+    /// every line
+    Lost,
+    /// Some line printed its output, or ran to the end, more than once
+    Duplicated,
+    /// pars stopped without running every line
+    Aborted,
+    /// pars did not finish after its input ended
+    Hung,
+}
+impl Outcome {
+    pub const ALL: [Outcome; 7] = [
+        Self::Recovered,
+        Self::RerunElsewhere,
+        Self::RerunSame,
+        Self::Lost,
+        Self::Duplicated,
+        Self::Aborted,
+        Self::Hung,
+    ];
+
+    /// The value given to `--reconnect-policy`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recovered => "recovered",
+            Self::RerunElsewhere => "rerun-elsewhere",
+            Self::RerunSame => "rerun-same",
+            Self::Lost => "lost",
+            Self::Duplicated => "duplicated",
+            Self::Aborted => "aborted",
+            Self::Hung => "hung",
+        }
+    }
+
+    /// Whether this outcome is what the `expected` policy asks for. A drop before any line
+    /// started leaves nothing to re-run, so the re-run policies also accept `Recovered`.
+    fn satisfies(self, expected: Outcome) -> bool {
+        self == expected
+            || (self == Self::Recovered
+                && matches!(expected, Self::RerunElsewhere | Self::RerunSame))
+    }
+}
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|outcome| outcome.as_str() == s)
+            .ok_or_else(|| {
+                let all: Vec<_> = Self::ALL.iter().map(Outcome::as_str).collect();
+                format!("expected one of {}", all.join(", "))
+            })
+    }
+}
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A line of an execution log: `<event> <name> <nanoseconds since the epoch>`, where the
+/// name is a line's, or for a `disconnect` event, when the connection was dropped
+#[derive(Debug, Clone)]
+struct Entry {
+    event: String,
+    name: String,
+    at: u128,
+}
+
+fn read_log(stand_in: &StandIn, port: usize) -> Vec<Entry> {
+    // a remote that never ran anything has no log
+    let log = fs::read_to_string(stand_in.log(port)).unwrap_or_default();
+    log.lines()
+        .map(|entry| {
+            let parts: Vec<_> = entry.split_whitespace().collect();
+            let [event, name, at] = parts[..] else {
+                panic!("malformed execution log entry {entry:?}");
+            };
+            Entry {
+                event: event.to_string(),
+                name: name.to_string(),
+                at: at.parse().expect("malformed time in execution log"),
+            }
+        })
+        .collect()
+}
+
+/// A line that logs its start, asks for its connection to be dropped (which only the first
+/// remote does, and only once), logs its end, and prints its name
+fn line(name: &str) -> String {
+    let log = |event| format!(r#"echo {event} {name} $(date +%s%N) >> "$STANDIN_LOG""#);
+    format!(
+        r#"sh -c '{}; touch "$STANDIN_DISCONNECT"; sleep {LINE_SECS}; {}; echo {name}'"#,
+        log("start"),
+        log("end"),
+    )
+}
+
+/// The line cut off by the drop `when` on a remote with the log `log`, if there was one
+fn cut_off(log: &[Entry], when: Disconnect) -> Option<String> {
+    let dropped_at = log
+        .iter()
+        .find(|entry| entry.event == "disconnect")
+        .map(|entry| entry.at)?;
+    let before = |event: &'static str| {
+        log.iter()
+            .filter(move |entry| entry.event == event && entry.at < dropped_at)
+    };
+
+    match when {
+        Disconnect::AtStartup => None,
+        // started, but not ended, before the drop
+        Disconnect::DuringLine => before("start")
+            .find(|start| !before("end").any(|end| end.name == start.name))
+            .map(|start| start.name.clone()),
+        // the last to end before the drop, whose exit status was lost
+        Disconnect::BetweenLines => before("end")
+            .max_by_key(|end| end.at)
+            .map(|end| end.name.clone()),
+    }
+}
+
+fn test_drop(when: Disconnect) {
+    let stand_in = stand_in()
+        .ports(2)
+        .disconnect(0, when)
+        .start()
+        .expect("Failed to start stand-in");
+    let distribution =
+        Distribution::Remote(vec![stand_in.remote_on(0, 1), stand_in.remote_on(1, 1)]);
+    let names: Vec<_> = (0..LINES).map(|i| format!("l{i}")).collect();
+
+    let mut program = launch_with(&stand_in, distribution, None);
+    program.run_cmds(names.iter().map(|name| line(name)));
+    program.close_input();
+    let hung = program.wait_timeout(HANG_AFTER).is_none();
+    if hung {
+        program
+            .signal_group(libc::SIGKILL)
+            .expect("Failed to kill pars");
+    }
+    let result = program.finish();
+    let logs = [read_log(&stand_in, 0), read_log(&stand_in, 1)];
+
+    let mut printed: BTreeMap<&str, usize> = names.iter().map(|n| (n.as_str(), 0)).collect();
+    for output in &result.stdout {
+        if let Some(count) = printed.get_mut(output.as_str()) {
+            *count += 1;
+        }
+    }
+    let count = |port: usize, event: &str, name: &str| {
+        logs[port]
+            .iter()
+            .filter(|entry| entry.event == event && entry.name == name)
+            .count()
+    };
+    let started = |name: &str| count(0, "start", name) + count(1, "start", name);
+
+    let cut_off = cut_off(&logs[0], when);
+    let outcome = if hung {
+        Outcome::Hung
+    } else if printed.values().any(|&n| n > 1) {
+        Outcome::Duplicated
+    } else if names.iter().any(|n| started(n) == 0) && !result.success() {
+        Outcome::Aborted
+    } else if printed.values().any(|&n| n == 0) {
+        Outcome::Lost
+    } else {
+        match &cut_off {
+            None if result.success() => Outcome::Recovered,
+            None => Outcome::Lost,
+            // a line cut off during its run has no end yet; one cut off after has one
+            Some(name) => {
+                let earlier = usize::from(when == Disconnect::BetweenLines);
+                match (
+                    count(0, "end", name).saturating_sub(earlier),
+                    count(1, "end", name),
+                ) {
+                    (0, 1) => Outcome::RerunElsewhere,
+                    (1, 0) => Outcome::RerunSame,
+                    (0, 0) if result.success() => Outcome::Recovered,
+                    (0, 0) => Outcome::Lost,
+                    _ => Outcome::Duplicated,
+                }
+            }
+        }
+    };
+
+    report::record("outcome", outcome);
+    report::record("exit", Exit::from(result.status));
+    if let Some(name) = &cut_off {
+        report::record("cut off", name);
+        report::record("times started", started(name));
+    }
+    if let Some(line) = result.stderr.first() {
+        report::record("stderr", line);
+    }
+
+    match config().reconnect_policy {
+        Some(expected) => assert!(
+            outcome.satisfies(expected),
+            "expected {expected} when the connection dropped {}, but got {outcome}\n\
+             printed: {printed:?}\nexecution logs: {logs:#?}",
+            when.as_str()
+        ),
+        None => assert!(!hung, "pars hung after the connection dropped"),
+    }
+}
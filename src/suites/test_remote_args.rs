@@ -0,0 +1,115 @@
+//! How pars handles malformed `--remote host:port/threads` arguments.
+//!
+//! Each should be rejected before any line runs, with an error message and a non-zero exit
+//! status rather than a panic. The arguments are written out by hand rather than built from a
+//! `RemoteHost`, which can only hold valid values.
+//!
+//! The same remote given twice is tried against a stand-in remote, so pars accepting it shows
+//! up as the line running rather than failing to connect. pars may either accept it, as other
+//! suites rely on to run more lines on a single real remote, or reject it cleanly. `-J`
+//! alongside `--remote` is valid, and should run the line in either order.
+use pars_testkit::{
+    assert::{assert_clean_error, assert_exit},
+    report, Exit, RunResult, TestCase,
+};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    let mut tests: Vec<_> = MALFORMED
+        .iter()
+        .map(|&(name, remote)| {
+            TestCase::new(format!("test_{name}"), move || test_malformed(remote))
+        })
+        .collect();
+    tests.extend([
+        TestCase::new("test_duplicate_remote", test_duplicate_remote),
+        TestCase::new("test_local_and_remote", test_local_and_remote),
+    ]);
+
+    tests
+}
+
+/// Each malformed value, and what is wrong with it
+const MALFORMED: &[(&str, &str)] = &[
+    ("missing_port", "localhost/1"),
+    ("empty_port", "localhost:/1"),
+    ("port_0", "localhost:0/1"),
+    ("port_above_65535", "localhost:65536/1"),
+    ("non_numeric_port", "localhost:ssh/1"),
+    ("missing_threads", "localhost:1234"),
+    ("zero_threads", "localhost:1234/0"),
+    ("negative_threads", "localhost:1234/-1"),
+    ("non_numeric_threads", "localhost:1234/two"),
+    ("missing_host", ":1234/1"),
+    ("empty", ""),
+];
+
+/// Runs pars with exactly `args`, and one line, which should only run if they are valid
+fn run_args(args: &[String], stand_in: Option<&StandIn>) -> RunResult {
+    report::record("args", args.join(" "));
+    let mut builder = ParsProgram::from_env().args(args);
+    if let Some(stand_in) = stand_in {
+        builder = stand_in.setup(builder);
+    }
+    let mut program = builder.spawn().expect("Failed to start pars");
+    // pars may exit before reading this, so it may fail to be written
+    let _ = program.try_write_input("echo ran\n");
+    program.finish()
+}
+
+fn test_malformed(remote: &str) {
+    let result = run_args(&["--remote".to_string(), remote.to_string()], None);
+    assert_clean_error(&result);
+}
+
+/// the same remote given twice, which pars may accept, running the line, or reject cleanly
+fn test_duplicate_remote() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let remote = stand_in.remote(1).to_string();
+    let args = ["--remote", &remote, "--remote", &remote].map(String::from);
+
+    let result = run_args(&args, Some(&stand_in));
+    let accepted = result.stdout == ["ran"];
+    report::record("duplicate", if accepted { "accepted" } else { "rejected" });
+    report::record("exit", result.exit());
+    if let Some(line) = result.stderr.first() {
+        report::record("stderr", line);
+    }
+    if accepted {
+        assert_exit(&result, Exit::Code(0));
+        assert!(
+            !result.stderr.iter().any(|line| line.contains("panicked")),
+            "pars panicked: {:?}",
+            result.stderr
+        );
+    } else {
+        assert_clean_error(&result);
+    }
+}
+
+/// `-J` alongside `--remote`, in either order, runs the line
+fn test_local_and_remote() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let remote = stand_in.remote(1).to_string();
+
+    for args in [
+        ["-J", "2", "--remote", &remote],
+        ["--remote", &remote, "-J", "2"],
+    ] {
+        let args = args.map(String::from);
+        let result = run_args(&args, Some(&stand_in));
+        assert_eq!(
+            result.stdout,
+            ["ran"],
+            "the line did not run with {args:?}, printing {:?}",
+            result.stderr
+        );
+        assert_exit(&result, Exit::Code(0));
+        assert!(
+            !result.stderr.iter().any(|line| line.contains("panicked")),
+            "pars panicked with {args:?}: {:?}",
+            result.stderr
+        );
+    }
+}
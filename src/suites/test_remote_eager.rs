@@ -0,0 +1,235 @@
+//! `--halt eager` across remotes: a line failing on one remote should stop every remote, both
+//! from starting new lines and from carrying on with the ones they are running.
+//!
+//! The remotes are two ports of one stand-in, and every line writes what it does, with a
+//! timestamp, to the execution log of the remote it runs on (see `StandIn::log`). So rather
+//! than inferring from missing output, the tests see which remote ran each line, when it
+//! started, and when it was last running, and record how long each remote took to stop.
+use std::{fs, thread, time::Duration};
+
+use pars_testkit::{report, TestCase};
+
+use crate::harness::*;
+
+/// How often running lines log that they are still running, in seconds
+const TICK_SECS: f64 = 0.05;
+
+/// How long the failing line runs before failing, long enough for the other remote to be
+/// running lines by then
+const FAIL_AFTER_SECS: f64 = 0.5;
+
+/// How soon after the failure remotes must stop starting lines
+const MAX_START_DELAY: Duration = Duration::from_millis(500);
+
+/// How soon after the failure remotes must stop running lines, allowing for a tick
+const MAX_STOP_DELAY: Duration = Duration::from_secs(1);
+
+/// Seconds each test may take, well beyond what the lines need
+const TIMEOUT: u64 = 30;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_no_new_lines/r1+1", || test_no_new_lines(1)).timeout(TIMEOUT),
+        TestCase::new("test_no_new_lines/r2+2", || test_no_new_lines(2)).timeout(TIMEOUT),
+        TestCase::new("test_running_lines_stop/r1+1", || {
+            test_running_lines_stop(1)
+        })
+        .timeout(TIMEOUT),
+        TestCase::new("test_running_lines_stop/r2+2", || {
+            test_running_lines_stop(2)
+        })
+        .timeout(TIMEOUT),
+    ]
+}
+
+/// What a line wrote to its remote's execution log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    Start,
+    /// still running
+    Tick,
+    /// about to exit with a failure
+    Fail,
+}
+
+/// A line of an execution log: `<event> <line> <nanoseconds since the epoch>`
+#[derive(Debug, Clone)]
+struct Entry {
+    event: Event,
+    line: String,
+    at: u128,
+}
+
+/// Every remote's execution log after a run
+#[derive(Debug)]
+struct Timeline {
+    remotes: Vec<Vec<Entry>>,
+}
+impl Timeline {
+    fn read(stand_in: &StandIn, remotes: usize) -> Self {
+        let remotes = (0..remotes)
+            .map(|i| {
+                // a remote that never ran anything has no log
+                let log = fs::read_to_string(stand_in.log(i)).unwrap_or_default();
+                log.lines().map(parse_entry).collect()
+            })
+            .collect();
+
+        Self { remotes }
+    }
+
+    /// The remote the failing line ran on, and when it failed
+    fn failure(&self) -> (usize, u128) {
+        self.remotes
+            .iter()
+            .enumerate()
+            .find_map(|(i, log)| {
+                log.iter()
+                    .find(|entry| entry.event == Event::Fail)
+                    .map(|entry| (i, entry.at))
+            })
+            .unwrap_or_else(|| panic!("the failing line never ran: {self:#?}"))
+    }
+
+    /// The entries of remote `i` after `at`
+    fn after(&self, i: usize, at: u128) -> impl Iterator<Item = &Entry> {
+        self.remotes[i].iter().filter(move |entry| entry.at > at)
+    }
+}
+
+fn parse_entry(entry: &str) -> Entry {
+    let parts: Vec<_> = entry.split_whitespace().collect();
+    let [event, line, at] = parts[..] else {
+        panic!("malformed execution log entry {entry:?}");
+    };
+    let event = match event {
+        "start" => Event::Start,
+        "tick" => Event::Tick,
+        "fail" => Event::Fail,
+        _ => panic!("unknown event in execution log entry {entry:?}"),
+    };
+
+    Entry {
+        event,
+        line: line.to_string(),
+        at: at.parse().expect("malformed time in execution log"),
+    }
+}
+
+/// Shell to append `event` for `line` to the execution log
+fn log(event: &str, line: &str) -> String {
+    format!(r#"echo {event} {line} $(date +%s%N) >> "$STANDIN_LOG""#)
+}
+
+/// A line that logs its start, then a tick every `TICK_SECS` for `secs` seconds
+fn worker(line: &str, secs: f64) -> String {
+    let ticks = (secs / TICK_SECS).ceil() as u32;
+    format!(
+        "sh -c '{}; i=0; while [ $i -lt {ticks} ]; do sleep {TICK_SECS}; {}; i=$((i+1)); done'",
+        log("start", line),
+        log("tick", line),
+    )
+}
+
+/// A line that logs its start, and fails after `FAIL_AFTER_SECS`
+fn failing(line: &str) -> String {
+    format!(
+        "sh -c '{}; sleep {FAIL_AFTER_SECS}; {}; exit 1'",
+        log("start", line),
+        log("fail", line),
+    )
+}
+
+/// Runs a failing line, then `workers` lines of `worker_secs` each, on two remotes of
+/// `threads` threads with `--halt eager`, and reads the remotes' logs once every line that
+/// could still be running has finished
+fn run(threads: u32, workers: usize, worker_secs: f64) -> Timeline {
+    let stand_in = stand_in()
+        .ports(2)
+        .start()
+        .expect("Failed to start stand-in");
+    let distribution = Distribution::Remote(vec![
+        stand_in.remote_on(0, threads),
+        stand_in.remote_on(1, threads),
+    ]);
+
+    let mut input = vec![failing("failing")];
+    input.extend((0..workers).map(|i| worker(&format!("w{i}"), worker_secs)));
+    let mut program = launch_with(&stand_in, distribution, Some(TerminationType::Eager));
+    program.run_cmds(&input);
+    program.finish();
+
+    // lines pars failed to stop are still running, and still logging
+    thread::sleep(Duration::from_secs_f64(worker_secs));
+    Timeline::read(&stand_in, 2)
+}
+
+/// Records how long after the failure each remote last logged anything
+fn record_stop_delays(timeline: &Timeline, failed_at: u128) {
+    for i in 0..timeline.remotes.len() {
+        let last = timeline.remotes[i].iter().map(|entry| entry.at).max();
+        let delay = last.map_or(0, |last| last.saturating_sub(failed_at));
+        report::record(
+            format!("remote {i} stopped after"),
+            format!("{}ms", delay / 1_000_000),
+        );
+    }
+}
+
+/// Asserts remote `i` started a line before `at`, as otherwise pars was never using it, and
+/// the test shows nothing about it being stopped
+fn assert_started_before(timeline: &Timeline, i: usize, at: u128) {
+    assert!(
+        timeline.remotes[i]
+            .iter()
+            .any(|entry| entry.event == Event::Start && entry.at < at),
+        "remote {i} ran nothing before the failure: {timeline:#?}"
+    );
+}
+
+/// no line starts on either remote once a line has failed on one
+fn test_no_new_lines(threads: u32) {
+    // short lines, so the other remote is always about to start another
+    let timeline = run(threads, 16 * threads as usize, 0.2);
+    let (failed_on, failed_at) = timeline.failure();
+    let other = 1 - failed_on;
+    report::record("failed on remote", failed_on);
+    record_stop_delays(&timeline, failed_at);
+
+    assert_started_before(&timeline, other, failed_at);
+    let deadline = failed_at + MAX_START_DELAY.as_nanos();
+    for i in [failed_on, other] {
+        let late: Vec<_> = timeline
+            .after(i, deadline)
+            .filter(|entry| entry.event == Event::Start)
+            .map(|entry| &entry.line)
+            .collect();
+        assert!(
+            late.is_empty(),
+            "remote {i} started {late:?} more than {MAX_START_DELAY:?} after a line failed \
+             on remote {failed_on}"
+        );
+    }
+}
+
+/// lines running on the other remote when a line fails are stopped
+fn test_running_lines_stop(threads: u32) {
+    // lines far longer than the failing one, so they are running when it fails
+    let timeline = run(threads, 2 * threads as usize, 3.0);
+    let (failed_on, failed_at) = timeline.failure();
+    let other = 1 - failed_on;
+    report::record("failed on remote", failed_on);
+    record_stop_delays(&timeline, failed_at);
+
+    assert_started_before(&timeline, other, failed_at);
+    let deadline = failed_at + MAX_STOP_DELAY.as_nanos();
+    for i in [failed_on, other] {
+        let mut late: Vec<_> = timeline.after(i, deadline).map(|e| &e.line).collect();
+        late.dedup();
+        assert!(
+            late.is_empty(),
+            "{late:?} on remote {i} kept running more than {MAX_STOP_DELAY:?} after a line \
+             failed on remote {failed_on}"
+        );
+    }
+}
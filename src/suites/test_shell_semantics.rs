@@ -0,0 +1,153 @@
+//! How pars splits a line into commands.
+//!
+//! pars splits lines with `pars_libs::parse_line`, which tokenises like a shell (quotes and
+//! backslashes are understood) and then splits on `;`. The commands are run directly, not
+//! through a shell, so operators such as `&&`, `|`, `>` and `$(...)` are passed through to the
+//! command as plain arguments. These tests record that behaviour for each case.
+//!
+//! Inputs are raw string literals, so each line is exactly what pars reads.
+use pars_testkit::{pars_test, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        test_single_quotes(),
+        test_double_quotes(),
+        test_nested_quotes(),
+        test_escaped_space(),
+        test_escaped_quotes(),
+        test_escaped_semicolon(),
+        test_empty_commands(),
+        test_and_or(),
+        test_pipe(),
+        test_redirect(),
+        test_command_substitution(),
+        test_variables(),
+        test_glob(),
+        test_explicit_shell(),
+    ]
+}
+
+pars_test! {
+    /// a quoted semicolon is part of the argument
+    test_single_quotes {
+        distribution: Distribution::Local(1),
+        input: [r"echo 'a; b'; echo c", r"echo 'x;y'"],
+        expect: ["a; b", "c", "x;y"],
+    }
+}
+
+pars_test! {
+    test_double_quotes {
+        distribution: Distribution::Local(1),
+        input: [r#"echo "a; b"; echo c"#, r#"echo "x;y""#],
+        expect: ["a; b", "c", "x;y"],
+    }
+}
+
+pars_test! {
+    test_nested_quotes {
+        distribution: Distribution::Local(1),
+        input: [r#"echo "it's"; echo 'say "hi"'"#],
+        expect: ["it's", r#"say "hi""#],
+    }
+}
+
+pars_test! {
+    test_escaped_space {
+        distribution: Distribution::Local(1),
+        input: [r"echo a\ \ b", r"echo a  b"],
+        expect: ["a  b", "a b"],
+    }
+}
+
+pars_test! {
+    test_escaped_quotes {
+        distribution: Distribution::Local(1),
+        input: [r#"echo \"a\" \'b\'"#],
+        expect: [r#""a" 'b'"#],
+    }
+}
+
+pars_test! {
+    /// the backslash is removed before splitting, so an escaped `;` still ends the command.
+    /// `b` is then run as a command, which does not exist
+    test_escaped_semicolon {
+        distribution: Distribution::Local(1),
+        input: [r"echo a\; b", r"echo c"],
+        expect: ["a", "c"],
+    }
+}
+
+pars_test! {
+    /// runs of `;` do not create empty commands
+    test_empty_commands {
+        distribution: Distribution::Local(1),
+        input: [r"echo 1;; echo 2", r"; echo 3 ;", r"echo 4 ; ; echo 5;"],
+        expect: ["1", "2", "3", "4", "5"],
+    }
+}
+
+pars_test! {
+    /// `&&` and `||` are arguments; `true` ignores its arguments and prints nothing
+    test_and_or {
+        distribution: Distribution::Local(1),
+        input: [r"echo a && echo b", r"echo a || echo b", r"true && echo hidden"],
+        expect: ["a && echo b", "a || echo b"],
+    }
+}
+
+pars_test! {
+    test_pipe {
+        distribution: Distribution::Local(1),
+        input: [r"echo a | tr a b", r"echo a|b"],
+        expect: ["a | tr a b", "a|b"],
+    }
+}
+
+pars_test! {
+    /// nothing is redirected, so no file is created
+    test_redirect {
+        distribution: Distribution::Local(1),
+        input: [r"echo a > /dev/null", r"echo b 2>&1", r"cat < /dev/null"],
+        expect: ["a > /dev/null", "b 2>&1"],
+    }
+}
+
+pars_test! {
+    test_command_substitution {
+        distribution: Distribution::Local(1),
+        input: [r"echo $(echo hi)", r"echo `echo hi`", r#"echo "$(echo hi)""#],
+        expect: ["$(echo hi)", "`echo hi`", "$(echo hi)"],
+    }
+}
+
+pars_test! {
+    test_variables {
+        distribution: Distribution::Local(1),
+        input: [r"echo $HOME", r#"echo "${HOME}""#],
+        expect: ["$HOME", "${HOME}"],
+    }
+}
+
+pars_test! {
+    test_glob {
+        distribution: Distribution::Local(1),
+        input: [r"echo *", r"echo /dev/nul?", r"echo ~"],
+        expect: ["*", "/dev/nul?", "~"],
+    }
+}
+
+pars_test! {
+    /// shell features are available by running a shell as the command
+    test_explicit_shell {
+        distribution: Distribution::Local(1),
+        input: [
+            r"sh -c 'echo a | tr a b'",
+            r"sh -c 'echo 1; echo 2'; echo 3",
+            r#"sh -c "true && echo 4""#,
+        ],
+        expect: ["b", "1", "2", "3", "4"],
+    }
+}
@@ -0,0 +1,185 @@
+//! What pars does when it is interrupted or terminated while lines are still running.
+//!
+//! Interrupting sends `SIGINT` to pars' whole process group, as Ctrl-C in a terminal would.
+//! Terminating sends `SIGTERM` to pars alone, as `kill` would. Either way pars should exit
+//! promptly, keep the output of lines that had already finished, and leave none of the
+//! commands it started running behind it.
+//!
+//! The remote tests check whether a remote command carried on by having it create a file once
+//! it finishes, so they assume the remote shares this machine's filesystem.
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use pars_testkit::{libc, report, Exit, Process, RunResult, TempDir, TestCase};
+
+use crate::harness::*;
+
+/// How long pars may take to exit after being signalled
+const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait for pars to start running its lines
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_interrupt_running_lines", || {
+            test_running_lines(Signal::Interrupt)
+        }),
+        TestCase::new("test_terminate_running_lines", || {
+            test_running_lines(Signal::Terminate)
+        }),
+        TestCase::new("test_interrupt_partial_output", || {
+            test_partial_output(Signal::Interrupt)
+        }),
+        TestCase::new("test_terminate_partial_output", || {
+            test_partial_output(Signal::Terminate)
+        }),
+        TestCase::new("test_interrupt_keeps_finished_output", || {
+            test_keeps_finished_output(Signal::Interrupt)
+        }),
+        TestCase::new("test_terminate_keeps_finished_output", || {
+            test_keeps_finished_output(Signal::Terminate)
+        }),
+        TestCase::new("test_interrupt_remote", || test_remote(Signal::Interrupt)).timeout(30),
+        TestCase::new("test_terminate_remote", || test_remote(Signal::Terminate)).timeout(30),
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    /// `SIGINT` to the process group, like Ctrl-C
+    Interrupt,
+    /// `SIGTERM` to pars alone, like `kill`
+    Terminate,
+}
+impl Signal {
+    fn send(self, program: &ParsProgram) {
+        match self {
+            Signal::Interrupt => program.signal_group(libc::SIGINT),
+            Signal::Terminate => program.signal(libc::SIGTERM),
+        }
+        .expect("Failed to signal pars");
+    }
+}
+
+/// Waits until pars has started at least `count` processes whose command line contains
+/// `command`, and returns every process descended from pars.
+fn wait_for_children(program: &ParsProgram, command: &str, count: usize) -> Vec<Process> {
+    let deadline = Instant::now() + START_TIMEOUT;
+    loop {
+        let children = program.descendants();
+        if children
+            .iter()
+            .filter(|p| p.cmdline.contains(command))
+            .count()
+            >= count
+        {
+            return children;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "pars did not start {count} `{command}` processes within {START_TIMEOUT:?}"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Signals pars and checks it exits promptly without leaving any of `children` running.
+fn signal_and_finish(mut program: ParsProgram, signal: Signal, children: &[Process]) -> RunResult {
+    signal.send(&program);
+    let exit = program.wait_timeout(EXIT_TIMEOUT);
+    // give pars a moment to clean up anything it stops after exiting
+    thread::sleep(Duration::from_millis(100));
+    let leaked: Vec<&Process> = children.iter().filter(|p| p.is_running()).collect();
+    // left running, these would hold pars' stdout open and stop `finish` from returning
+    for process in &leaked {
+        process.kill();
+    }
+
+    let Some(exit) = exit else {
+        panic!("pars was still running {EXIT_TIMEOUT:?} after {signal:?}");
+    };
+    report::record("exit", exit);
+    let leaked: Vec<String> = leaked.iter().map(|p| p.to_string()).collect();
+    assert!(
+        leaked.is_empty(),
+        "pars left processes running after {signal:?}:\n  {}",
+        leaked.join("\n  ")
+    );
+
+    program.finish()
+}
+
+/// Signals pars while it is running lines, which should all be stopped or waited on.
+fn test_running_lines(signal: Signal) {
+    let mut program = launch(Distribution::Local(2), None);
+    program.run_cmds(["sleep 30", "sleep 30", "sleep 30"]);
+    let children = wait_for_children(&program, "sleep", 2);
+
+    let result = signal_and_finish(program, signal, &children);
+    assert!(
+        result.exit() != Exit::Code(0),
+        "pars exited successfully after {signal:?}, with lines left unfinished"
+    );
+}
+
+/// Signals pars part way through a line. Whether the output the line had printed so far is
+/// flushed is recorded, but the rest of it must never appear.
+fn test_partial_output(signal: Signal) {
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds(["echo start; sleep 30; echo end"]);
+    let children = wait_for_children(&program, "sleep", 1);
+
+    let result = signal_and_finish(program, signal, &children);
+    let flushed = match result.stdout.as_slice() {
+        [] => false,
+        [line] if line == "start" => true,
+        output => panic!("unexpected output after {signal:?}: {output:?}"),
+    };
+    report::record("partial output flushed", flushed);
+}
+
+/// Signals pars after some lines have finished, whose output should already be printed.
+fn test_keeps_finished_output(signal: Signal) {
+    let mut program = launch(Distribution::Local(1), None);
+    program.run_cmds(["echo 1", "echo 2", "sleep 30", "echo 3"]);
+    let children = wait_for_children(&program, "sleep", 1);
+
+    let result = signal_and_finish(program, signal, &children);
+    assert_eq!(
+        result.stdout,
+        ["1", "2"],
+        "output of finished lines was lost after {signal:?}"
+    );
+}
+
+/// Signals pars while lines are running on a remote. Each remote line would create a file
+/// after a short sleep, and none should appear once pars has exited.
+fn test_remote(signal: Signal) {
+    let dir = TempDir::new("pars-autotest-signals").unwrap();
+    let markers: Vec<_> = (0..2).map(|i| dir.join(i.to_string())).collect();
+
+    let remote = remote(2);
+    let mut program = launch(Distribution::Remote(vec![remote]), None);
+    program.run_cmds(
+        markers
+            .iter()
+            .map(|marker| format!("sleep 3; touch {}", marker.display())),
+    );
+    // the commands run on the remote, so wait for the ssh connection instead
+    let children = wait_for_children(&program, "ssh", 1);
+    thread::sleep(Duration::from_millis(500));
+
+    let result = signal_and_finish(program, signal, &children);
+    thread::sleep(Duration::from_secs(4));
+    let finished: Vec<_> = markers.iter().filter(|m| m.exists()).collect();
+
+    assert!(
+        finished.is_empty(),
+        "{} of {} remote lines kept running after {signal:?}; pars printed {:?}",
+        finished.len(),
+        markers.len(),
+        result.stdout
+    );
+}
@@ -0,0 +1,155 @@
+//! How pars deals with ssh failing to connect to a remote.
+//!
+//! Each test runs against its own stand-in ssh server (see `pars_testkit::StandIn`), with a
+//! throwaway key pair and host key, so no real remote or account is needed. When ssh cannot
+//! authenticate, pars should report it and exit with a failure, without running the line or
+//! panicking.
+//!
+//! The stand-in's `ssh` fails as real ssh does, which `test_ssh_messages` checks. It refuses an
+//! unknown host as ssh does without a terminal, where ssh would otherwise ask whether to trust
+//! it.
+use pars_testkit::{report, ClientKey, Exit, KnownHost, RunResult, TestCase};
+
+use crate::harness::*;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_successful_auth", test_successful_auth),
+        TestCase::new("test_missing_key", || {
+            test_auth_fails(ClientKey::Missing, KnownHost::Known)
+        }),
+        TestCase::new("test_wrong_key", || {
+            test_auth_fails(ClientKey::Unauthorized, KnownHost::Known)
+        }),
+        TestCase::new("test_unknown_host_key", || {
+            test_auth_fails(ClientKey::Authorized, KnownHost::Unknown)
+        }),
+        TestCase::new("test_changed_host_key", || {
+            test_auth_fails(ClientKey::Authorized, KnownHost::Changed)
+        }),
+        TestCase::new("test_ssh_messages", test_ssh_messages),
+    ]
+}
+
+/// Runs `input` on a single stand-in remote thread
+fn run(stand_in: &StandIn, input: &[&str]) -> RunResult {
+    let distribution = Distribution::Remote(vec![stand_in.remote(1)]);
+    let mut program = launch_with(stand_in, distribution, None);
+    program.run_cmds(input);
+    program.finish()
+}
+
+/// with a known host and an authorized key, lines run as normal
+fn test_successful_auth() {
+    let stand_in = stand_in().start().expect("Failed to start stand-in");
+    let result = run(&stand_in, &["echo hello", "echo world; echo again"]);
+
+    assert_eq!(result.stdout, ["hello", "world", "again"]);
+    assert!(result.success(), "pars failed with {}", result.exit());
+}
+
+/// ssh fails before running anything, which pars should report cleanly
+fn test_auth_fails(client_key: ClientKey, known_host: KnownHost) {
+    let stand_in = stand_in()
+        .client_key(client_key)
+        .known_host(known_host)
+        .start()
+        .expect("Failed to start stand-in");
+    let result = run(&stand_in, &["touch ran; echo ran"]);
+    if let Some(line) = result.stderr.first() {
+        report::record("stderr", line);
+    }
+
+    assert!(
+        !stand_in.home().join("ran").exists() && result.stdout.is_empty(),
+        "the line ran, printing {:?}",
+        result.stdout
+    );
+    assert!(
+        !result.success(),
+        "pars exited successfully, though ssh could not connect"
+    );
+    assert!(
+        !result.stderr.iter().any(|line| line.contains("panicked")),
+        "pars panicked: {:?}",
+        result.stderr
+    );
+}
+
+/// the stand-in's `ssh` prints what OpenSSH does when it cannot connect, and exits with 255
+fn test_ssh_messages() {
+    for (client_key, known_host) in [
+        (ClientKey::Missing, KnownHost::Known),
+        (ClientKey::Unauthorized, KnownHost::Known),
+        (ClientKey::Authorized, KnownHost::Unknown),
+        (ClientKey::Authorized, KnownHost::Changed),
+    ] {
+        let stand_in = stand_in()
+            .client_key(client_key)
+            .known_host(known_host)
+            .start()
+            .expect("Failed to start stand-in");
+        let port = stand_in.addr().port();
+        let key = stand_in.home().join(".ssh/cs6991/cs6991-id");
+        let known_hosts = stand_in.home().join(".ssh/known_hosts");
+        let host = format!("[127.0.0.1]:{port}");
+        let expected = match (client_key, known_host) {
+            (ClientKey::Missing, _) => vec![
+                format!(
+                    "Warning: Identity file {} not accessible: No such file or directory.",
+                    key.display()
+                ),
+                "autotest@127.0.0.1: Permission denied (publickey).".to_string(),
+            ],
+            (_, KnownHost::Unknown) => vec!["Host key verification failed.".to_string()],
+            (_, KnownHost::Changed) => vec![
+                "@    WARNING: REMOTE HOST IDENTIFICATION HAS CHANGED!     @".to_string(),
+                "IT IS POSSIBLE THAT SOMEONE IS DOING SOMETHING NASTY!".to_string(),
+                format!(
+                    "Add correct host key in {} to get rid of this message.",
+                    known_hosts.display()
+                ),
+                format!(
+                    "  ssh-keygen -f \"{}\" -R \"{host}\"",
+                    known_hosts.display()
+                ),
+                format!("Host key for {host} has changed and you have requested strict checking."),
+                "Host key verification failed.".to_string(),
+            ],
+            _ => vec!["autotest@127.0.0.1: Permission denied (publickey).".to_string()],
+        };
+
+        let builder = ParsProgram::builder("ssh")
+            .args(["-p", &port.to_string(), "autotest@127.0.0.1", "-i"])
+            .arg(&key)
+            .args(["--", "touch", "ran"]);
+        let mut ssh = stand_in
+            .setup(builder)
+            .spawn()
+            .expect("Failed to start ssh");
+        let result = ssh.finish();
+
+        let case = format!("{client_key:?} key, {known_host:?} host");
+        assert_eq!(
+            result.exit(),
+            Exit::Code(255),
+            "ssh exited wrongly with a {case}"
+        );
+        for line in &expected {
+            assert!(
+                result.stderr.contains(line),
+                "ssh did not print {line:?} with a {case}, printing {:?}",
+                result.stderr
+            );
+        }
+        assert_eq!(
+            result.stderr.last(),
+            expected.last(),
+            "ssh did not end with the expected error with a {case}"
+        );
+        assert!(
+            !stand_in.home().join("ran").exists(),
+            "ssh ran the command with a {case}"
+        );
+    }
+}
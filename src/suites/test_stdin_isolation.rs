@@ -0,0 +1,60 @@
+//! Checks commands cannot read pars' own input.
+//!
+//! A command given pars' stdin would swallow the lines after it, so they never run. Each
+//! command should instead see an empty or closed stdin, and the lines after it should still
+//! run. A command left waiting on an open stdin hangs the test, so these tests have a short
+//! timeout.
+use pars_testkit::{assert::assert_lines, Matrix, TestCase};
+
+use crate::harness::*;
+
+/// Seconds each test may take, well beyond what the lines need
+const TIMEOUT: u64 = 15;
+
+pub fn tests() -> Vec<TestCase> {
+    let matrix = Matrix::new()
+        .local(1..=1)
+        .distribution(Distribution::Remote(vec![remote(1)]));
+
+    let mut tests = Vec::new();
+    for (name, input, expected) in SCENARIOS {
+        tests.extend(matrix.expand(name, input, |_, result| {
+            assert_lines(&result.stdout, expected)
+        }));
+    }
+
+    tests
+        .into_iter()
+        .map(|test| test.timeout(TIMEOUT))
+        .collect()
+}
+
+/// Each scenario's name, input, and expected output
+const SCENARIOS: &[(&str, &[&str], &[&str])] = &[
+    (
+        // a stolen stdin would be echoed back by `cat`
+        "cat",
+        &["cat", "echo 1", "echo 2"],
+        &["1", "2"],
+    ),
+    (
+        "cat_then_more",
+        &[r"sh -c 'cat; echo eof'", "echo 1"],
+        &["eof", "1"],
+    ),
+    (
+        "read",
+        &[r#"sh -c 'read x; echo "got [$x]"'"#, "echo 1", "echo 2"],
+        &["got []", "1", "2"],
+    ),
+    (
+        "head",
+        &["head -n1", "echo 1", "echo 2", "echo 3"],
+        &["1", "2", "3"],
+    ),
+    (
+        "several_readers",
+        &["cat", "echo 1", "head -n1", "echo 2", "cat", "echo 3"],
+        &["1", "2", "3"],
+    ),
+];
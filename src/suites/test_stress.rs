@@ -0,0 +1,172 @@
+//! Large inputs, large outputs, and many lines finishing at once.
+//!
+//! Each test checks no output is lost or interleaved part way through a line, and that the
+//! peak memory of pars stays bounded. The throughput and peak RSS of pars are recorded in the
+//! report.
+use std::{collections::HashMap, time::Duration};
+
+use pars_testkit::{
+    assert::{assert_lines, assert_lines_unordered},
+    report, RunResult, TestCase,
+};
+
+use crate::harness::*;
+
+/// How often pars' memory use is sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+/// Memory pars may use beyond the output it has to hold on to, in KiB
+const RSS_OVERHEAD_KB: u64 = 64 * 1024;
+
+pub fn tests() -> Vec<TestCase> {
+    vec![
+        TestCase::new("test_many_lines", test_many_lines).timeout(120),
+        TestCase::new("test_many_lines_ordered", test_many_lines_ordered).timeout(120),
+        TestCase::new("test_megabytes_per_line", test_megabytes_per_line).timeout(60),
+        TestCase::new("test_simultaneous_finish", test_simultaneous_finish).timeout(60),
+    ]
+}
+
+fn launch_monitored(distribution: Distribution) -> ParsProgram {
+    ParsProgram::from_env()
+        .distribution(distribution)
+        .monitor(SAMPLE_INTERVAL)
+        .spawn()
+        .expect("Failed to start pars")
+}
+
+/// Records the throughput and peak memory of a run, and checks the memory used is at most
+/// `buffered_bytes` (the output pars may need to hold at once) plus a fixed overhead.
+fn check_resources(result: &RunResult, lines: usize, buffered_bytes: usize) {
+    let secs = result.duration.as_secs_f64();
+    let bytes = result.raw_stdout.len();
+    report::record("duration", format!("{secs:.2}s"));
+    report::record("lines/s", format!("{:.0}", lines as f64 / secs));
+    report::record(
+        "output MiB/s",
+        format!("{:.2}", bytes as f64 / secs / 1048576.0),
+    );
+
+    let stats = result.proc_stats.as_ref().expect("pars was not monitored");
+    report::record("peak rss", format!("{} KiB", stats.peak_rss_kb));
+    report::record("peak threads", stats.peak_threads);
+    report::record("peak children", stats.peak_children);
+
+    let limit = RSS_OVERHEAD_KB + 2 * buffered_bytes as u64 / 1024;
+    assert!(
+        stats.peak_rss_kb <= limit,
+        "pars used {} KiB of memory, more than the limit of {limit} KiB",
+        stats.peak_rss_kb
+    );
+}
+
+/// Checks every line of output is one of `rows`, that the rows of each line came out
+/// together, and returns how many times each row was printed.
+fn count_contiguous_rows<'a>(result: &'a RunResult, rows: &[String]) -> HashMap<&'a str, usize> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut previous: Option<&str> = None;
+    for (i, line) in result.stdout.iter().enumerate() {
+        assert!(
+            rows.contains(line),
+            "line {i} of the output was broken or interleaved: {:?}...",
+            &line[..line.len().min(80)]
+        );
+        if previous != Some(line) {
+            assert!(
+                !counts.contains_key(line.as_str()),
+                "output of a line was split up by another line, at line {i}"
+            );
+        }
+        *counts.entry(line).or_default() += 1;
+        previous = Some(line);
+    }
+
+    counts
+}
+
+/// thousands of short lines across 8 threads
+fn test_many_lines() {
+    const LINES: usize = 5000;
+    let input: Vec<_> = (0..LINES).map(|i| format!("echo {i}")).collect();
+    let expected: Vec<_> = (0..LINES).map(|i| i.to_string()).collect();
+
+    let mut program = launch_monitored(Distribution::Local(8));
+    program.run_cmds(&input);
+    let result = program.finish();
+
+    assert_lines_unordered(&result.stdout, &expected);
+    check_resources(&result, LINES, 0);
+}
+
+/// thousands of lines on one thread, where the order must be kept
+fn test_many_lines_ordered() {
+    const LINES: usize = 2000;
+    let input: Vec<_> = (0..LINES).map(|i| format!("echo {i}; true")).collect();
+    let expected: Vec<_> = (0..LINES).map(|i| i.to_string()).collect();
+
+    let mut program = launch_monitored(Distribution::Local(1));
+    program.run_cmds(&input);
+    let result = program.finish();
+
+    assert_lines(&result.stdout, &expected);
+    check_resources(&result, LINES, 0);
+}
+
+/// 8 lines, each printing about 2MB in rows of 100 characters, all at once
+fn test_megabytes_per_line() {
+    const ROW: usize = 100;
+    const LINE_BYTES: usize = ROW * 20 * 1024;
+    let chars = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+    let input: Vec<_> = chars
+        .iter()
+        .map(|c| {
+            format!(
+                r#"sh -c "head -c {LINE_BYTES} /dev/zero | tr '\000' {c} | fold -w {ROW}; echo""#
+            )
+        })
+        .collect();
+    let rows: Vec<_> = chars.iter().map(|c| c.to_string().repeat(ROW)).collect();
+
+    let mut program = launch_monitored(Distribution::Local(8));
+    program.run_cmds(&input);
+    let result = program.finish();
+
+    let counts = count_contiguous_rows(&result, &rows);
+    for row in &rows {
+        assert_eq!(
+            counts.get(row.as_str()).copied().unwrap_or_default(),
+            LINE_BYTES / ROW,
+            "output of a line was lost"
+        );
+    }
+    check_resources(&result, chars.len(), chars.len() * LINE_BYTES);
+}
+
+/// 64 lines, each printing 150KiB, all finishing at the same moment
+fn test_simultaneous_finish() {
+    const THREADS: u32 = 64;
+    const ROWS: usize = 50;
+    // each row is `NN-` 1024 times, then a newline
+    const LINE_BYTES: usize = ROWS * (3 * 1024 + 1);
+    let rows: Vec<_> = (0..THREADS)
+        .map(|i| format!("{i:02}-").repeat(1024))
+        .collect();
+    let input: Vec<_> = rows
+        .iter()
+        .map(|row| format!(r#"sh -c "sleep 1; yes {row} | head -n {ROWS}""#))
+        .collect();
+
+    let mut program = launch_monitored(Distribution::Local(THREADS));
+    program.run_cmds(&input);
+    let result = program.finish();
+
+    let counts = count_contiguous_rows(&result, &rows);
+    for row in &rows {
+        assert_eq!(
+            counts.get(row.as_str()).copied().unwrap_or_default(),
+            ROWS,
+            "output of a line was lost"
+        );
+    }
+    // every line is running at once, so pars may hold each one's output
+    check_resources(&result, rows.len(), THREADS as usize * LINE_BYTES);
+}